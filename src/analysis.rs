@@ -0,0 +1,270 @@
+use options::Options;
+use terrain;
+use terrain::TerrainSpec;
+use world;
+
+
+const PLATEAU_TOLERANCE: f32 = 1e-6;
+
+#[derive(RustcEncodable)]
+pub struct Cell {
+    x: u32,
+    y: u32
+}
+
+#[derive(Clone, RustcEncodable)]
+pub struct Optimum {
+    x:              u32,
+    y:              u32,
+    fitness:        f32,
+    plateau_size:   usize,
+    basin_size:     usize,
+    basin_fraction: f64
+}
+
+#[derive(RustcEncodable)]
+pub struct Zone {
+    cells:    usize,
+    fraction: f64,
+    boundary: Vec<Cell>
+}
+
+#[derive(RustcEncodable)]
+pub struct Analysis {
+    x_axis:         String,
+    y_axis:         String,
+    resolution:     usize,
+    tree_height:    u32,
+    lion_speed:     u32,
+    color:          u32,
+    global_optimum: Optimum,
+    local_optima:   Vec<Optimum>,
+    ridge:          Vec<Cell>,
+    infeasible:     Zone
+}
+
+struct Grid {
+    spec:      TerrainSpec,
+    x_values:  Vec<u32>,
+    y_values:  Vec<u32>,
+    fitnesses: Vec<Vec<f32>>,
+    ascents:   Vec<Vec<Option<(usize, usize)>>>
+}
+
+impl Grid {
+    fn cell(&self, row: usize, col: usize) -> Cell {
+        Cell { x: self.x_values[col], y: self.y_values[row] }
+    }
+
+    fn neighbours(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let size = self.spec.resolution as isize;
+
+        let mut results = vec![];
+
+        for d_row in -1..2 {
+            for d_col in -1..2 {
+                let (n_row, n_col) = (row as isize + d_row, col as isize + d_col);
+
+                if (d_row, d_col) != (0, 0) && n_row >= 0 && n_col >= 0 && n_row < size && n_col < size {
+                    results.push((n_row as usize, n_col as usize));
+                }
+            }
+        }
+
+        results
+    }
+
+    fn ascent(&self, row: usize, col: usize) -> Option<(usize, usize)> {
+        self.ascents[row][col]
+    }
+
+    // The neighbour reached by the steepest ascent, or None at a local maximum.
+    fn steepest_neighbour(&self, row: usize, col: usize) -> Option<(usize, usize)> {
+        let fitness = self.fitnesses[row][col];
+
+        self.neighbours(row, col).into_iter()
+            .filter(|&(n_row, n_col)| self.fitnesses[n_row][n_col] > fitness + PLATEAU_TOLERANCE)
+            .fold(None, |best: Option<(usize, usize)>, (n_row, n_col)| {
+                match best {
+                    Some((b_row, b_col)) if self.fitnesses[b_row][b_col] >= self.fitnesses[n_row][n_col] => best,
+                    _ => Some((n_row, n_col))
+                }
+            })
+    }
+}
+
+pub fn analyze(spec: &TerrainSpec, options: &Options) -> Analysis {
+    let traits    = terrain::create_trait_matrix(&spec, &options);
+    let fitnesses = traits.iter().map(|row| {
+        row.iter().map(|values| {
+            world::calculate_phenotype_fitness(&options, values.color, values.height, values.speed)
+        }).collect::<Vec<f32>>()
+    }).collect::<Vec<Vec<f32>>>();
+
+    let mut grid = Grid {
+        spec:      *spec,
+        x_values:  spec.x_values(),
+        y_values:  spec.y_values(),
+        fitnesses: fitnesses,
+        ascents:   vec![]
+    };
+
+    grid.ascents = (0..spec.resolution).map(|row| {
+        (0..spec.resolution).map(|col| grid.steepest_neighbour(row, col)).collect()
+    }).collect();
+
+    let mut local_optima = find_local_optima(&grid);
+
+    local_optima.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+
+    let global_optimum = local_optima[0].clone();
+
+    let ridge = find_boundary(&grid, |row, col| {
+        traits[row][col].height >= options.tree_height
+    });
+
+    let infeasible_cells = traits.iter().fold(0, |acc, row| {
+        acc + row.iter().filter(|values| values.speed == 0).count()
+    });
+
+    let infeasible = Zone {
+        cells:    infeasible_cells,
+        fraction: infeasible_cells as f64 / (spec.resolution * spec.resolution) as f64,
+        boundary: find_boundary(&grid, |row, col| traits[row][col].speed == 0)
+    };
+
+    Analysis {
+        x_axis:         spec.x_axis.phenotype.label().to_string(),
+        y_axis:         spec.y_axis.phenotype.label().to_string(),
+        resolution:     spec.resolution,
+        tree_height:    options.tree_height,
+        lion_speed:     options.lion_speed,
+        color:          options.color,
+        global_optimum: global_optimum,
+        local_optima:   local_optima,
+        ridge:          ridge,
+        infeasible:     infeasible
+    }
+}
+
+// Cells that touch at the same fitness are one plateau. A plateau with no
+// strictly higher neighbour is a single optimum; any other plateau is a
+// shoulder whose flat cells leave through one of its rising edges. Every
+// cell then climbs by steepest ascent into the basin of the optimum it
+// reaches.
+fn find_local_optima(grid: &Grid) -> Vec<Optimum> {
+    let size = grid.spec.resolution;
+
+    let mut visited = vec![vec![false; size]; size];
+    let mut climbs  = grid.ascents.clone();
+
+    let mut plateau_ids: Vec<Vec<Option<usize>>> = vec![vec![None; size]; size];
+    let mut plateaus:    Vec<Vec<(usize, usize)>> = vec![];
+
+    for row in 0..size {
+        for col in 0..size {
+            if visited[row][col] || grid.ascent(row, col).is_some() {
+                continue;
+            }
+
+            let fitness     = grid.fitnesses[row][col];
+            let mut members = vec![];
+            let mut pending = vec![(row, col)];
+
+            visited[row][col] = true;
+
+            while let Some((m_row, m_col)) = pending.pop() {
+                members.push((m_row, m_col));
+
+                for (n_row, n_col) in grid.neighbours(m_row, m_col) {
+                    let is_same_plateau = !visited[n_row][n_col]
+                        && (grid.fitnesses[n_row][n_col] - fitness).abs() <= PLATEAU_TOLERANCE;
+
+                    if is_same_plateau {
+                        visited[n_row][n_col] = true;
+                        pending.push((n_row, n_col));
+                    }
+                }
+            }
+
+            let exit = members.iter().cloned().find(|&(m_row, m_col)| grid.ascent(m_row, m_col).is_some());
+
+            match exit {
+                Some(exit) => {
+                    for &(m_row, m_col) in &members {
+                        if climbs[m_row][m_col].is_none() {
+                            climbs[m_row][m_col] = Some(exit);
+                        }
+                    }
+                },
+                None => {
+                    for &(m_row, m_col) in &members {
+                        plateau_ids[m_row][m_col] = Some(plateaus.len());
+                    }
+
+                    plateaus.push(members);
+                }
+            }
+        }
+    }
+
+    let mut basin_ids   = plateau_ids.clone();
+    let mut basin_sizes = vec![0; plateaus.len()];
+
+    for row in 0..size {
+        for col in 0..size {
+            let mut path     = vec![];
+            let mut position = (row, col);
+
+            while basin_ids[position.0][position.1].is_none() {
+                path.push(position);
+                position = climbs[position.0][position.1].unwrap();
+            }
+
+            let id = basin_ids[position.0][position.1];
+
+            for (p_row, p_col) in path {
+                basin_ids[p_row][p_col] = id;
+            }
+
+            basin_sizes[id.unwrap()] += 1;
+        }
+    }
+
+    plateaus.iter().enumerate().map(|(id, members)| {
+        let (row, col) = members[0];
+        let cell       = grid.cell(row, col);
+
+        Optimum {
+            x:              cell.x,
+            y:              cell.y,
+            fitness:        grid.fitnesses[row][col],
+            plateau_size:   members.len(),
+            basin_size:     basin_sizes[id],
+            basin_fraction: basin_sizes[id] as f64 / (size * size) as f64
+        }
+    }).collect::<Vec<Optimum>>()
+}
+
+// Cells inside the region that border a cell outside of it.
+fn find_boundary<F>(grid: &Grid, is_inside: F) -> Vec<Cell> where F: Fn(usize, usize) -> bool {
+    let mut results = vec![];
+
+    for row in 0..grid.spec.resolution {
+        for col in 0..grid.spec.resolution {
+            if !is_inside(row, col) {
+                continue;
+            }
+
+            let is_boundary = grid.neighbours(row, col).into_iter().any(|(n_row, n_col)| {
+                !is_inside(n_row, n_col)
+            });
+
+            if is_boundary {
+                results.push(grid.cell(row, col));
+            }
+        }
+    }
+
+    results
+}
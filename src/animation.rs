@@ -0,0 +1,153 @@
+use std::fs;
+use std::io;
+use std::io::Write;
+
+use gnuplot;
+use gnuplot::AxesCommon;
+
+use histogram;
+use solution_space::Landscape;
+use terrain::TerrainSpec;
+use world::World;
+
+
+pub struct Animation {
+    directory: String,
+    interval:  u32,
+    spec:      TerrainSpec,
+    terminal:  String,
+    extension: &'static str,
+    landscape: Option<Landscape>
+}
+
+impl Animation {
+    pub fn new(
+        directory: &str,
+        interval:  u32,
+        spec:      &TerrainSpec,
+        terminal:  &str,
+        extension: &'static str
+    ) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+
+        Ok(Animation {
+            directory: directory.to_string(),
+            interval:  interval,
+            spec:      *spec,
+            terminal:  terminal.to_string(),
+            extension: extension,
+            landscape: None
+        })
+    }
+
+    pub fn record(&mut self, world: &World) {
+        if world.generation % self.interval != 0 {
+            return;
+        }
+
+        self.render_landscape_frame(&world);
+        self.render_density_frame(&world);
+    }
+
+    pub fn print_instructions<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "Rendered animation frames to {:?}, e.g. `convert -delay 10 {}/landscape_*.{} landscape.gif`",
+            self.directory,
+            self.directory,
+            self.extension
+        )
+    }
+
+    fn landscape(&mut self, world: &World) -> &Vec<Vec<f32>> {
+        let spec      = &self.spec;
+        let landscape = self.landscape.get_or_insert_with(|| {
+            Landscape::new(&spec, &world.options)
+        });
+
+        landscape.track(&world.options);
+
+        &landscape.matrix
+    }
+
+    fn render_landscape_frame(&mut self, world: &World) {
+        let destination = format!("{}/landscape_{:05}.{}", self.directory, world.generation, self.extension);
+
+        let fitnesses = self.landscape(&world).concat();
+
+        let spec   = self.spec;
+        let x_axis = spec.x_axis;
+        let y_axis = spec.y_axis;
+
+        let x_values = world.tower.iter().map(|giraffe| {
+            x_axis.phenotype.value_of(&giraffe)
+        }).collect::<Vec<u32>>();
+
+        let y_values = world.tower.iter().map(|giraffe| {
+            y_axis.phenotype.value_of(&giraffe)
+        }).collect::<Vec<u32>>();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(&self.terminal, &destination);
+        figure.axes2d()
+            .set_title(&format!("Generation {}", world.generation), &vec![])
+            .set_x_label(x_axis.phenotype.label(), &vec![])
+            .set_y_label(y_axis.phenotype.label(), &vec![])
+            .set_cb_label("Fitness", &vec![])
+            .set_x_range(gnuplot::Fix(x_axis.min as f64), gnuplot::Fix(x_axis.max as f64))
+            .set_y_range(gnuplot::Fix(y_axis.min as f64), gnuplot::Fix(y_axis.max as f64))
+            .image(
+                &fitnesses,
+                spec.resolution,
+                spec.resolution,
+                Some((x_axis.min as f64, y_axis.min as f64, x_axis.max as f64, y_axis.max as f64)),
+                &[]
+            )
+            .points(
+                &x_values,
+                &y_values,
+                &[gnuplot::Caption("Giraffes"), gnuplot::Color("black"), gnuplot::PointSymbol('O'), gnuplot::PointSize(0.3)]
+            );
+
+        figure.show();
+    }
+
+    fn render_density_frame(&self, world: &World) {
+        let destination = format!("{}/density_{:05}.{}", self.directory, world.generation, self.extension);
+
+        let leg_lengths = world.tower.iter().map(|giraffe| {
+            giraffe.leg_length() as f64
+        }).collect::<Vec<f64>>();
+
+        let neck_lengths = world.tower.iter().map(|giraffe| {
+            giraffe.neck_length() as f64
+        }).collect::<Vec<f64>>();
+
+        let x_bins = histogram::NECK_BINS;
+        let y_bins = histogram::LEG_BINS;
+        let width  = (x_bins.size as usize * x_bins.count) as f64;
+        let height = (y_bins.size as usize * y_bins.count) as f64;
+        let counts = histogram::density(&neck_lengths, x_bins, &leg_lengths, y_bins);
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(&self.terminal, &destination);
+        figure.axes2d()
+            .set_title(&format!("Generation {}", world.generation), &vec![])
+            .set_x_label("Neck Length", &vec![])
+            .set_y_label("Leg Length", &vec![])
+            .set_cb_label("Giraffes", &vec![])
+            .set_x_range(gnuplot::Fix(0.0), gnuplot::Fix(width))
+            .set_y_range(gnuplot::Fix(0.0), gnuplot::Fix(height))
+            .image(
+                &counts,
+                y_bins.count,
+                x_bins.count,
+                Some((0.0, 0.0, width, height)),
+                &[]
+            );
+
+        figure.show();
+    }
+}
@@ -1,13 +1,38 @@
 use giraffe_lib::random_proportion;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, RustcEncodable)]
 pub enum BlendingMode {
     Mean,
     OnePointCrossover,
     UniformCrossover
 }
 
+impl BlendingMode {
+    pub fn from_name(name: &str) -> Option<BlendingMode> {
+        match name {
+            "mean"     => Some(BlendingMode::Mean),
+            "onepoint" => Some(BlendingMode::OnePointCrossover),
+            "uniform"  => Some(BlendingMode::UniformCrossover),
+            _          => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            BlendingMode::Mean              => "mean",
+            BlendingMode::OnePointCrossover => "onepoint",
+            BlendingMode::UniformCrossover  => "uniform"
+        }
+    }
+}
+
+// Chromosomes a giraffe doesn't carry draw nothing, so they leave the random
+// stream of a seeded run as it was.
 pub fn blend_chromosomes(a: &Vec<u8>, b: &Vec<u8>, mode: BlendingMode) -> Vec<u8> {
+    if a.is_empty() {
+        return vec![];
+    }
+
     match mode {
         BlendingMode::Mean              => mean(&a, &b),
         BlendingMode::OnePointCrossover => one_point_crossover(&a, &b),
@@ -0,0 +1,107 @@
+use giraffe::Giraffe;
+use traits::HasHeight;
+
+#[derive(Copy, Clone, Debug, RustcEncodable)]
+pub enum ForageModel {
+    TreeHeight,
+    Canopy
+}
+
+impl ForageModel {
+    pub fn from_name(name: &str) -> Option<ForageModel> {
+        match name {
+            "height" => Some(ForageModel::TreeHeight),
+            "canopy" => Some(ForageModel::Canopy),
+            _        => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ForageModel::TreeHeight => "height",
+            ForageModel::Canopy     => "canopy"
+        }
+    }
+}
+
+const LEAF_BIN_SIZE: u32 = 25;
+const BROWSE_REACH:  f32 = 0.25;
+
+// (crown height relative to tree height, share of total foliage, crown depth)
+const SPECIES: [(f32, f32, f32); 3] = [
+    (0.6, 0.25, 0.15),
+    (1.0, 0.50, 0.15),
+    (1.4, 0.25, 0.15)
+];
+
+pub struct Canopy {
+    leaves: Vec<f32>
+}
+
+impl Canopy {
+    pub fn new(tree_height: u32, herd_size: usize) -> Self {
+        let tallest_crown = SPECIES.iter().fold(0.0, |acc: f32, &(height, _, depth)| {
+            acc.max(height * (1.0 + depth))
+        });
+
+        let num_bins   = (tallest_crown * tree_height as f32) as usize / LEAF_BIN_SIZE as usize + 1;
+        let mut leaves = vec![0.0; num_bins];
+
+        for &(height, share, depth) in SPECIES.iter() {
+            let crown_center = height * tree_height as f32;
+            let crown_bottom = bin_index(crown_center * (1.0 - depth));
+            let crown_top    = bin_index(crown_center * (1.0 + depth)).min(num_bins - 1);
+            let foliage      = share * herd_size as f32;
+            let per_bin      = foliage / (crown_top - crown_bottom + 1) as f32;
+
+            for bin in crown_bottom..(crown_top + 1) {
+                leaves[bin] += per_bin;
+            }
+        }
+
+        Canopy { leaves: leaves }
+    }
+
+    // Each giraffe browses the band between (1 - BROWSE_REACH) of its height
+    // and its head, eating upward from the bottom of the band. Giraffes browse
+    // in order of height, shortest first, so the lower leaves are depleted by
+    // the time taller giraffes reach them. A giraffe needs one unit of leaves
+    // to be fully fed, so the canopy as a whole can feed exactly one herd.
+    pub fn forage(&self, tower: &Vec<Giraffe>) -> Vec<f32> {
+        let mut leaves = self.leaves.clone();
+        let mut order  = (0..tower.len()).collect::<Vec<usize>>();
+
+        order.sort_by_key(|&i| tower[i].height());
+
+        let mut intakes = vec![0.0; tower.len()];
+
+        for i in order {
+            let (bottom, top) = self.browsing_band(tower[i].height());
+
+            for bin in bottom..(top + 1) {
+                let eaten = leaves[bin].min(1.0 - intakes[i]);
+
+                leaves[bin] -= eaten;
+                intakes[i]  += eaten;
+
+                if intakes[i] >= 1.0 {
+                    break;
+                }
+            }
+        }
+
+        intakes
+    }
+
+    fn browsing_band(&self, height: u32) -> (usize, usize) {
+        let last_bin = self.leaves.len() - 1;
+        let top      = bin_index(height as f32).min(last_bin);
+        let bottom   = bin_index(height as f32 * (1.0 - BROWSE_REACH)).min(top);
+
+        (bottom, top)
+    }
+}
+
+fn bin_index(height: f32) -> usize {
+    (height.max(0.0) as u32 / LEAF_BIN_SIZE) as usize
+}
@@ -0,0 +1,371 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use gnuplot;
+use gnuplot::AxesCommon;
+
+use giraffe;
+use traits::HasColor;
+use world::World;
+
+
+const MAX_CLUSTERS:   usize = 6;
+const MAX_ITERATIONS: usize = 50;
+
+// The mean simplified silhouette a split needs before the tower counts as
+// more than one cluster.
+const SPLIT_SILHOUETTE: f64 = 0.6;
+
+// How far, in scaled units, a centroid may be from where a track was last
+// seen, at most a few generations ago, and still continue it.
+const TRACK_DISTANCE: f64 = 0.1;
+const TRACK_MEMORY:   usize = 10;
+
+const TRACK_COLORS: [&'static str; 8] = ["black", "red", "blue", "green", "orange", "purple", "brown", "cyan"];
+
+type Point = [f64; 3];
+
+#[derive(Clone, Debug)]
+pub struct Cluster {
+    pub track: usize,
+    pub size:  usize,
+    pub legs:  f64,
+    pub neck:  f64,
+    pub color: f64
+}
+
+// The tower clustered in (legs, neck, color) space every generation. Each
+// axis is scaled by its maximum so they weigh the same. The number of
+// clusters is chosen by k-means for k from 1 to 6, taking the k with the best
+// simplified silhouette once a split is clear enough. Clusters continue the
+// nearest track seen in the last few generations, so a split shows as a new
+// track branching off.
+pub struct ClusterHistory {
+    generations: Vec<Vec<Cluster>>,
+    last_seen:   Vec<(usize, Point)>
+}
+
+impl ClusterHistory {
+    pub fn new() -> Self {
+        ClusterHistory {
+            generations: vec![],
+            last_seen:   vec![]
+        }
+    }
+
+    pub fn record(&mut self, world: &World) {
+        let points = world.tower.iter().map(|giraffe| {
+            [
+                giraffe.leg_length() as f64 / giraffe::MAX_LEG_LENGTH as f64,
+                giraffe.neck_length() as f64 / giraffe::MAX_NECK_LENGTH as f64,
+                giraffe.color() as f64 / giraffe::MAX_COLOR as f64
+            ]
+        }).collect::<Vec<Point>>();
+
+        let (centroids, assignments) = cluster(&points);
+
+        let mut sizes = vec![0; centroids.len()];
+
+        for &assignment in &assignments {
+            sizes[assignment] += 1;
+        }
+
+        let mut order = (0..centroids.len()).collect::<Vec<usize>>();
+
+        order.sort_by(|&a, &b| sizes[b].cmp(&sizes[a]));
+
+        let tracks = self.match_tracks(&order.iter().map(|&i| centroids[i]).collect());
+
+        let clusters = order.iter().zip(tracks).map(|(&i, track)| {
+            Cluster {
+                track: track,
+                size:  sizes[i],
+                legs:  centroids[i][0] * giraffe::MAX_LEG_LENGTH as f64,
+                neck:  centroids[i][1] * giraffe::MAX_NECK_LENGTH as f64,
+                color: centroids[i][2] * giraffe::MAX_COLOR as f64
+            }
+        }).collect::<Vec<Cluster>>();
+
+        for cluster in &clusters {
+            self.last_seen[cluster.track] = (self.generations.len(), scale(cluster));
+        }
+
+        self.generations.push(clusters);
+    }
+
+    // Largest clusters first, each takes the nearest unclaimed track seen
+    // recently, if close enough.
+    fn match_tracks(&mut self, centroids: &Vec<Point>) -> Vec<usize> {
+        let generation = self.generations.len();
+
+        // Tracks lost for longer are closed.
+        let mut claimed = self.last_seen.iter().map(|&(seen, _)| {
+            seen + TRACK_MEMORY < generation
+        }).collect::<Vec<bool>>();
+
+        centroids.iter().map(|centroid| {
+            let nearest = self.last_seen.iter().enumerate()
+                .filter(|&(track, _)| !claimed[track])
+                .map(|(track, &(_, ref point))| (track, distance(&centroid, &point)))
+                .filter(|&(_, distance)| distance <= TRACK_DISTANCE)
+                .fold(None, |best: Option<(usize, f64)>, candidate| match best {
+                    Some(best) if best.1 <= candidate.1 => Some(best),
+                    _                                   => Some(candidate)
+                });
+
+            let track = match nearest {
+                Some((track, _)) => track,
+                None             => {
+                    self.last_seen.push((generation, *centroid));
+                    claimed.push(false);
+                    self.last_seen.len() - 1
+                }
+            };
+
+            claimed[track] = true;
+            track
+        }).collect()
+    }
+
+    pub fn latest(&self) -> Option<&Vec<Cluster>> {
+        self.generations.last()
+    }
+
+    pub fn print_summary<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let most = self.generations.iter().enumerate().fold((0, 0), |(generation, most), (i, clusters)| {
+            if clusters.len() > most { (i, clusters.len()) } else { (generation, most) }
+        });
+
+        writeln!(out, "clusters: at most {} (generation {}), {} tracks", most.1, most.0, self.last_seen.len())?;
+
+        if let Some(clusters) = self.latest() {
+            for cluster in clusters {
+                writeln!(
+                    out,
+                    "  track {}: {} giraffes, legs: {:.1}, neck: {:.1}, color: {:.1}",
+                    cluster.track,
+                    cluster.size,
+                    cluster.legs,
+                    cluster.neck,
+                    cluster.color
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn export_csv(&self, destination_file: &str) -> io::Result<()> {
+        let mut file = File::create(destination_file)?;
+
+        writeln!(file, "generation,clusters,track,size,legs,neck,color")?;
+
+        for (generation, clusters) in self.generations.iter().enumerate() {
+            for cluster in clusters {
+                writeln!(
+                    file,
+                    "{},{},{},{},{},{},{}",
+                    generation,
+                    clusters.len(),
+                    cluster.track,
+                    cluster.size,
+                    cluster.legs,
+                    cluster.neck,
+                    cluster.color
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // The number of clusters, then each track's centroid height and color.
+    pub fn generate_figure(&self, terminal: &str, destination_file: &str) {
+        let x: Vec<f64> = (0..self.generations.len()).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let counts = self.generations.iter().map(|clusters| clusters.len()).collect::<Vec<usize>>();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+        figure.axes2d()
+            .set_pos_grid(3, 1, 0)
+            .set_y_label("Clusters", &vec![])
+            .lines(&x, &counts, &[gnuplot::Caption("Clusters"), gnuplot::Color("black")]);
+
+        let panels: [(&str, fn(&Cluster) -> f64); 2] = [
+            ("Height", |cluster| cluster.legs + cluster.neck),
+            ("Color", |cluster| cluster.color)
+        ];
+
+        for (panel, &(label, value)) in panels.iter().enumerate() {
+            let axes = figure.axes2d()
+                .set_pos_grid(3, 1, panel as u32 + 1)
+                .set_y_label(label, &vec![]);
+
+            if panel == panels.len() - 1 {
+                axes.set_x_label("Generation", &vec![]);
+            }
+
+            for track in 0..self.last_seen.len() {
+                let (generations, values): (Vec<f64>, Vec<f64>) = self.generations.iter().enumerate()
+                    .filter_map(|(generation, clusters)| {
+                        clusters.iter().find(|cluster| cluster.track == track).map(|cluster| {
+                            (generation as f64, value(cluster))
+                        })
+                    })
+                    .unzip();
+
+                axes.points(
+                    &generations,
+                    &values,
+                    &[gnuplot::PointSymbol('.'), gnuplot::Color(TRACK_COLORS[track % TRACK_COLORS.len()])]
+                );
+            }
+        }
+
+        figure.show();
+    }
+}
+
+fn scale(cluster: &Cluster) -> Point {
+    [
+        cluster.legs / giraffe::MAX_LEG_LENGTH as f64,
+        cluster.neck / giraffe::MAX_NECK_LENGTH as f64,
+        cluster.color / giraffe::MAX_COLOR as f64
+    ]
+}
+
+fn distance(a: &Point, b: &Point) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+fn nearest(point: &Point, centroids: &Vec<Point>) -> usize {
+    (1..centroids.len()).fold(0, |best, i| {
+        if distance(&point, &centroids[i]) < distance(&point, &centroids[best]) { i } else { best }
+    })
+}
+
+fn mean(points: &Vec<&Point>) -> Point {
+    let n = points.len().max(1) as f64;
+
+    points.iter().fold([0.0; 3], |acc, point| {
+        [acc[0] + point[0] / n, acc[1] + point[1] / n, acc[2] + point[2] / n]
+    })
+}
+
+// Centroids and every point's cluster for the best k. Seeding and iterating
+// are deterministic, so clustering never draws from the simulation's random
+// stream.
+pub fn cluster(points: &Vec<Point>) -> (Vec<Point>, Vec<usize>) {
+    let single = (vec![mean(&points.iter().collect())], vec![0; points.len()]);
+
+    (2..MAX_CLUSTERS.min(points.len()) + 1).map(|k| {
+        let (centroids, assignments) = k_means(&points, k);
+        let silhouette               = simplified_silhouette(&points, &centroids, &assignments);
+
+        (silhouette, (centroids, assignments))
+    }).filter(|&(silhouette, (ref centroids, _))| {
+        centroids.len() > 1 && silhouette >= SPLIT_SILHOUETTE
+    }).fold(None, |best: Option<(f64, _)>, candidate| {
+        match best {
+            Some(best) if best.0 >= candidate.0 => Some(best),
+            _                                   => Some(candidate)
+        }
+    }).map_or(single, |(_, clustering)| clustering)
+}
+
+// Seeded from the point nearest the mean, then each point furthest from the
+// seeds so far.
+fn k_means(points: &Vec<Point>, k: usize) -> (Vec<Point>, Vec<usize>) {
+    let center = mean(&points.iter().collect());
+
+    let mut centroids = vec![points[nearest(&center, &points)]];
+
+    while centroids.len() < k {
+        let furthest = (0..points.len()).fold(0, |best, i| {
+            let gap      = distance(&points[i], &centroids[nearest(&points[i], &centroids)]);
+            let best_gap = distance(&points[best], &centroids[nearest(&points[best], &centroids)]);
+
+            if gap > best_gap { i } else { best }
+        });
+
+        centroids.push(points[furthest]);
+    }
+
+    let mut assignments = points.iter().map(|point| nearest(&point, &centroids)).collect::<Vec<usize>>();
+
+    for _ in 0..MAX_ITERATIONS {
+        centroids = (0..k).map(|cluster| {
+            let members = points.iter().zip(&assignments)
+                .filter(|&(_, &assignment)| assignment == cluster)
+                .map(|(point, _)| point)
+                .collect::<Vec<&Point>>();
+
+            if members.is_empty() { centroids[cluster] } else { mean(&members) }
+        }).collect();
+
+        let next = points.iter().map(|point| nearest(&point, &centroids)).collect::<Vec<usize>>();
+
+        if next == assignments {
+            break;
+        }
+
+        assignments = next;
+    }
+
+    // Seeds can share a point and leave a cluster without members, which is
+    // dropped rather than scored and recorded at its seed.
+    let kept = (0..k).filter(|&cluster| assignments.contains(&cluster)).collect::<Vec<usize>>();
+
+    let assignments = assignments.iter().map(|&assignment| {
+        kept.iter().position(|&cluster| cluster == assignment).unwrap()
+    }).collect();
+
+    (kept.iter().map(|&cluster| centroids[cluster]).collect(), assignments)
+}
+
+// The silhouette measured against centroids instead of every other point,
+// which keeps it linear in the herd size.
+fn simplified_silhouette(points: &Vec<Point>, centroids: &Vec<Point>, assignments: &Vec<usize>) -> f64 {
+    let total = points.iter().zip(assignments).fold(0.0, |acc, (point, &assignment)| {
+        let own   = distance(&point, &centroids[assignment]);
+        let other = (0..centroids.len()).filter(|&i| i != assignment).fold(::std::f64::MAX, |closest, i| {
+            closest.min(distance(&point, &centroids[i]))
+        });
+
+        let widest = own.max(other);
+
+        acc + if widest > 0.0 { (other - own) / widest } else { 0.0 }
+    });
+
+    total / points.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clusters_without_members_are_dropped() {
+        let mut points = vec![[0.2, 0.2, 0.2]; 5];
+
+        points.push([0.8, 0.8, 0.8]);
+
+        let (centroids, assignments) = k_means(&points, 3);
+
+        assert_eq!(centroids, vec![[0.2, 0.2, 0.2], [0.8, 0.8, 0.8]]);
+        assert_eq!(assignments, vec![0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn identical_points_form_one_cluster() {
+        let (centroids, assignments) = cluster(&vec![[0.5, 0.5, 0.5]; 4]);
+
+        assert_eq!(centroids.len(), 1);
+        assert_eq!(assignments, vec![0; 4]);
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use giraffe::Giraffe;
+use traits::HasColor;
+use traits::HasHeight;
+use traits::HasSpeed;
+
+#[derive(Copy, Clone, Debug, RustcEncodable)]
+pub enum Competition {
+    None,
+    Crowding,
+    Partitioning
+}
+
+impl Competition {
+    pub fn from_name(name: &str) -> Option<Competition> {
+        match name {
+            "none"         => Some(Competition::None),
+            "crowding"     => Some(Competition::Crowding),
+            "partitioning" => Some(Competition::Partitioning),
+            _              => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Competition::None         => "none",
+            Competition::Crowding     => "crowding",
+            Competition::Partitioning => "partitioning"
+        }
+    }
+}
+
+const PHENOTYPE_BIN_SIZE: u32 = 50;
+const HEIGHT_CLASS_SIZE:  u32 = 100;
+
+pub fn apply_competition(
+    competition: Competition,
+    strength:    f32,
+    tower:       &Vec<Giraffe>,
+    fitnesses:   Vec<f32>
+) -> Vec<f32> {
+    match competition {
+        Competition::None         => fitnesses,
+        Competition::Crowding     => crowding(strength, &tower, fitnesses),
+        Competition::Partitioning => partitioning(strength, &tower, fitnesses)
+    }
+}
+
+// Fitness sharing: giraffes that land in the same (color, height, speed) bin
+// split their fitness between them.
+fn crowding(strength: f32, tower: &Vec<Giraffe>, fitnesses: Vec<f32>) -> Vec<f32> {
+    let bins = tower.iter().map(|giraffe| {
+        (
+            giraffe.color()  / PHENOTYPE_BIN_SIZE,
+            giraffe.height() / PHENOTYPE_BIN_SIZE,
+            giraffe.speed()  / PHENOTYPE_BIN_SIZE
+        )
+    }).collect::<Vec<(u32, u32, u32)>>();
+
+    let counts = count_occurrences(&bins);
+
+    bins.iter().zip(fitnesses).map(|(bin, fitness)| {
+        fitness / (counts[bin] as f32).powf(strength)
+    }).collect::<Vec<f32>>()
+}
+
+// Every occupied height class holds an equal share of the browse, so
+// giraffes in sparsely populated classes are rewarded and giraffes in
+// crowded ones are penalized.
+fn partitioning(strength: f32, tower: &Vec<Giraffe>, fitnesses: Vec<f32>) -> Vec<f32> {
+    let classes = tower.iter().map(|giraffe| {
+        giraffe.height() / HEIGHT_CLASS_SIZE
+    }).collect::<Vec<u32>>();
+
+    let counts         = count_occurrences(&classes);
+    let expected_count = tower.len() as f32 / counts.len() as f32;
+
+    classes.iter().zip(fitnesses).map(|(class, fitness)| {
+        fitness * (expected_count / counts[class] as f32).powf(strength)
+    }).collect::<Vec<f32>>()
+}
+
+fn count_occurrences<T: Eq + Hash + Copy>(keys: &Vec<T>) -> HashMap<T, u32> {
+    let mut counts = HashMap::new();
+
+    for key in keys.iter() {
+        *counts.entry(*key).or_insert(0) += 1;
+    }
+
+    counts
+}
@@ -0,0 +1,304 @@
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use toml;
+
+use blending::BlendingMode;
+use canopy::ForageModel;
+use competition::Competition;
+use mating::{Inbreeding, Mating, MatingTrait};
+use mutation::MutationDecay;
+use selection::Selection;
+use options::{Options, OptionsError};
+
+
+const CONFIG_FILE:        &'static str = "giraffe.toml";
+const ENVIRONMENT_PREFIX: &'static str = "GIRAFFE_";
+
+// The layered settings, named after their command line flags.
+pub const SETTINGS: [&'static str; 17] = [
+    "length", "size", "color", "speed", "height", "mutrate", "mutdecay", "blendmode", "forage",
+    "competition", "compstrength", "selection", "markers", "mating", "matetrait", "candidates", "inbreeding"
+];
+
+pub const SETTING_NAMES: &'static str = "length | size | color | speed | height | mutrate | mutdecay | \
+                                         blendmode | forage | competition | compstrength | selection | markers | \
+                                         mating | matetrait | candidates | inbreeding";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    Default,
+    File(String),
+    Environment,
+    Flag
+}
+
+#[derive(Clone, Debug)]
+pub struct Setting {
+    pub value:  String,
+    pub source: Source
+}
+
+impl Setting {
+    // How the setting was given, for error messages.
+    pub fn option(&self, key: &str) -> String {
+        match self.source {
+            Source::Default         => key.to_string(),
+            Source::File(ref path)  => format!("{} in {}", key, path),
+            Source::Environment     => environment_variable(key),
+            Source::Flag            => format!("--{}", key)
+        }
+    }
+}
+
+// Settings gathered from giraffe.toml in the home directory, then the working
+// directory, then GIRAFFE_* environment variables and finally the command
+// line, each layer overriding the ones before it. Unset settings fall back to
+// the defaults.
+#[derive(Clone)]
+pub struct Config {
+    settings: Vec<(&'static str, Setting)>
+}
+
+impl Config {
+    pub fn load() -> Result<Config, OptionsError> {
+        let mut paths = vec![];
+
+        if let Ok(home) = env::var("HOME") {
+            paths.push(Path::new(&home).join(CONFIG_FILE));
+        }
+
+        paths.push(PathBuf::from(CONFIG_FILE));
+
+        Config::load_from(&paths, |name| env::var(name).ok())
+    }
+
+    // Reads the files in order, missing ones skipped, then looks up each
+    // setting's environment variable.
+    pub fn load_from<F>(paths: &Vec<PathBuf>, env_lookup: F) -> Result<Config, OptionsError>
+        where F: Fn(&str) -> Option<String>
+    {
+        let mut config = Config { settings: vec![] };
+
+        for path in paths {
+            config.load_file(path)?;
+        }
+
+        for key in SETTINGS.iter() {
+            if let Some(value) = env_lookup(&environment_variable(key)) {
+                config.set(key, value, Source::Environment);
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), OptionsError> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(_)   => return Ok(())
+        };
+
+        let path         = path.to_string_lossy().into_owned();
+        let invalid      = |message: String| OptionsError::InvalidConfig { path: path.clone(), message: message };
+        let mut contents = String::new();
+
+        file.read_to_string(&mut contents).map_err(|error| invalid(error.to_string()))?;
+
+        let mut parser = toml::Parser::new(&contents);
+
+        let table = parser.parse().ok_or_else(|| {
+            let error       = &parser.errors[0];
+            let (line, col) = parser.to_linecol(error.lo);
+
+            invalid(format!("line {}, column {}: {}", line + 1, col + 1, error.desc))
+        })?;
+
+        for (key, value) in table {
+            let setting = SETTINGS.iter().find(|&&setting| setting == key).ok_or_else(|| {
+                invalid(format!("unknown setting {:?}, expected one of: {}", key, SETTING_NAMES))
+            })?;
+
+            let value = match value {
+                toml::Value::String(value)  => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value)   => value.to_string(),
+                _                           => return Err(invalid(format!("{} must be a number or a string", key)))
+            };
+
+            self.set(setting, value, Source::File(path.clone()));
+        }
+
+        Ok(())
+    }
+
+    pub fn set(&mut self, key: &'static str, value: String, source: Source) {
+        self.settings.retain(|&(setting, _)| setting != key);
+        self.settings.push((key, Setting { value: value, source: source }));
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Setting> {
+        self.settings.iter().find(|&&(setting, _)| setting == key).map(|&(_, ref setting)| setting)
+    }
+
+    pub fn source(&self, key: &str) -> Source {
+        self.get(key).map_or(Source::Default, |setting| setting.source.clone())
+    }
+
+    pub fn show(&self, options: &Options) {
+        for key in SETTINGS.iter() {
+            println!("{:<12} = {:<10} {}", key, effective_value(key, &options), self.describe_source(key));
+        }
+    }
+
+    fn describe_source(&self, key: &str) -> String {
+        match self.source(key) {
+            Source::Default     => "default".to_string(),
+            Source::File(path)  => path,
+            Source::Environment => environment_variable(key),
+            Source::Flag        => format!("--{}", key)
+        }
+    }
+}
+
+fn environment_variable(key: &str) -> String {
+    format!("{}{}", ENVIRONMENT_PREFIX, key.to_uppercase())
+}
+
+// Proportions are shown as the percentages they're given in.
+fn percentage(proportion: f32) -> String {
+    ((proportion as f64 * 1e8).round() / 1e6).to_string()
+}
+
+fn effective_value(key: &str, options: &Options) -> String {
+    match key {
+        "length"       => options.simulation_length.to_string(),
+        "size"         => options.herd_size.to_string(),
+        "color"        => options.color.to_string(),
+        "speed"        => options.lion_speed.to_string(),
+        "height"       => options.tree_height.to_string(),
+        "mutrate"      => percentage(options.mutation_rate),
+        "mutdecay"     => MutationDecay::name(&options.mutation_decay).to_string(),
+        "blendmode"    => BlendingMode::name(&options.blending_mode).to_string(),
+        "forage"       => ForageModel::name(&options.forage_model).to_string(),
+        "competition"  => Competition::name(&options.competition).to_string(),
+        "compstrength" => percentage(options.competition_strength),
+        "selection"    => Selection::name(&options.selection).to_string(),
+        "markers"      => options.markers.to_string(),
+        "mating"       => Mating::name(&options.mating).to_string(),
+        "matetrait"    => MatingTrait::name(&options.mating_trait).to_string(),
+        "candidates"   => options.mate_candidates.to_string(),
+        "inbreeding"   => Inbreeding::name(&options.inbreeding).to_string(),
+        _              => String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::io::Write;
+    use std::process;
+
+    // A config file unique to this test run, removed when dropped.
+    struct TempFile {
+        path: PathBuf
+    }
+
+    impl TempFile {
+        fn new(name: &str, contents: &str) -> Self {
+            let path = env::temp_dir().join(format!("giraffe_{}_{}.toml", process::id(), name));
+
+            File::create(&path).unwrap().write_all(contents.as_bytes()).unwrap();
+
+            TempFile { path: path }
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    fn no_environment(_: &str) -> Option<String> {
+        None
+    }
+
+    fn value(config: &Config, key: &str) -> String {
+        config.get(key).unwrap().value.clone()
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let home    = TempFile::new("home", "length = 10\nsize = 20\ncolor = 30\nspeed = 40\n");
+        let working = TempFile::new("working", "size = 21\ncolor = 31\nspeed = 41\n");
+        let paths   = vec![home.path.clone(), working.path.clone()];
+
+        let mut config = Config::load_from(&paths, |name| {
+            match name {
+                "GIRAFFE_COLOR" | "GIRAFFE_SPEED" => Some("32".to_string()),
+                _                                 => None
+            }
+        }).unwrap();
+
+        config.set("speed", "43".to_string(), Source::Flag);
+
+        assert_eq!(value(&config, "length"), "10");
+        assert_eq!(value(&config, "size"), "21");
+        assert_eq!(value(&config, "color"), "32");
+        assert_eq!(value(&config, "speed"), "43");
+
+        assert_eq!(config.source("length"), Source::File(home.path.to_string_lossy().into_owned()));
+        assert_eq!(config.source("size"), Source::File(working.path.to_string_lossy().into_owned()));
+        assert_eq!(config.source("color"), Source::Environment);
+        assert_eq!(config.source("speed"), Source::Flag);
+        assert_eq!(config.source("height"), Source::Default);
+    }
+
+    #[test]
+    fn missing_files_are_skipped() {
+        let config = Config::load_from(&vec![env::temp_dir().join("giraffe_missing.toml")], no_environment).unwrap();
+
+        assert!(config.get("length").is_none());
+    }
+
+    #[test]
+    fn unknown_settings_are_invalid() {
+        let file = TempFile::new("unknown", "lenght = 10\n");
+
+        match Config::load_from(&vec![file.path.clone()], no_environment) {
+            Err(OptionsError::InvalidConfig { message, .. }) => assert!(message.contains("lenght")),
+            _                                                => panic!("expected an invalid config")
+        }
+    }
+
+    #[test]
+    fn non_scalar_values_are_invalid() {
+        let file = TempFile::new("array", "length = [10, 20]\n");
+
+        match Config::load_from(&vec![file.path.clone()], no_environment) {
+            Err(OptionsError::InvalidConfig { message, .. }) => assert!(message.contains("length")),
+            _                                                => panic!("expected an invalid config")
+        }
+    }
+
+    #[test]
+    fn show_names_where_each_setting_came_from() {
+        let file = TempFile::new("show", "length = 10\n");
+
+        let mut config = Config::load_from(&vec![file.path.clone()], |name| {
+            if name == "GIRAFFE_SIZE" { Some("20".to_string()) } else { None }
+        }).unwrap();
+
+        config.set("color", "30".to_string(), Source::Flag);
+
+        assert_eq!(config.describe_source("length"), file.path.to_string_lossy());
+        assert_eq!(config.describe_source("size"), "GIRAFFE_SIZE");
+        assert_eq!(config.describe_source("color"), "--color");
+        assert_eq!(config.describe_source("speed"), "default");
+    }
+}
@@ -0,0 +1,218 @@
+use std::io;
+use std::io::Write;
+
+use statistics::Statistics;
+
+
+// A trait mean has converged once it stays within tolerance, a fraction of
+// its target, for window generations, or until the target changes first.
+#[derive(Copy, Clone, Debug)]
+pub struct Criteria {
+    pub tolerance: f64,
+    pub window:    usize
+}
+
+#[derive(RustcEncodable)]
+pub struct Adaptation {
+    pub generation: u32,
+    pub from:       u32,
+    pub to:         u32,
+    pub lag:        Option<u32>,
+    pub overshoot:  f64
+}
+
+#[derive(RustcEncodable)]
+pub struct TraitConvergence {
+    pub name:                       String,
+    pub generations_to_convergence: Option<u32>,
+    pub overshoot:                  f64,
+    pub steady_state_error:         f64,
+    pub adaptations:                Vec<Adaptation>
+}
+
+#[derive(RustcEncodable)]
+pub struct Convergence {
+    pub tolerance: f64,
+    pub window:    usize,
+    pub traits:    Vec<TraitConvergence>
+}
+
+impl Convergence {
+    pub fn new(statistics: &Statistics, criteria: Criteria) -> Self {
+        let traits = statistics.trait_histories().into_iter().map(|(label, means, targets)| {
+            measure_trait(label, &means, &targets, criteria)
+        }).collect();
+
+        Convergence {
+            tolerance: criteria.tolerance,
+            window:    criteria.window,
+            traits:    traits
+        }
+    }
+
+    pub fn print_summary<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(
+            out,
+            "convergence, within {}% of target for {} generations:",
+            self.tolerance * 100.0,
+            self.window
+        )?;
+
+        for measured in &self.traits {
+            writeln!(
+                out,
+                "  {}: converged: {}, overshoot: {:.1}, steady-state error: {:.1}",
+                measured.name,
+                describe_generation(measured.generations_to_convergence),
+                measured.overshoot,
+                measured.steady_state_error
+            )?;
+
+            for adaptation in &measured.adaptations {
+                writeln!(
+                    out,
+                    "    target {} -> {} at generation {}: lag: {}, overshoot: {:.1}",
+                    adaptation.from,
+                    adaptation.to,
+                    adaptation.generation,
+                    adaptation.lag.map_or("never".to_string(), |lag| {
+                        format!("{} generation{}", lag, if lag == 1 { "" } else { "s" })
+                    }),
+                    adaptation.overshoot
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn describe_generation(generation: Option<u32>) -> String {
+    generation.map_or("never".to_string(), |generation| format!("generation {}", generation))
+}
+
+fn is_within(mean: f64, target: u32, tolerance: f64) -> bool {
+    (mean - target as f64).abs() <= tolerance * target as f64
+}
+
+// The first generation in start..end from which the mean stays within
+// tolerance for the window, or through to end when the target changes there.
+// A shorter streak cut off by the end of the run hasn't settled yet.
+fn settle(means: &Vec<f64>, targets: &Vec<u32>, start: usize, end: usize, criteria: Criteria) -> Option<usize> {
+    let mut settled = None;
+
+    for i in start..end {
+        if !is_within(means[i], targets[i], criteria.tolerance) {
+            settled = None;
+            continue;
+        }
+
+        let from = *settled.get_or_insert(i);
+
+        if i + 1 - from >= criteria.window {
+            return settled;
+        }
+    }
+
+    if end < means.len().min(targets.len()) { settled } else { None }
+}
+
+// How far the mean went past the target in the direction it started
+// approaching from.
+fn overshoot(means: &Vec<f64>, targets: &Vec<u32>, start: usize, end: usize) -> f64 {
+    let target    = targets[start] as f64;
+    let direction = if means[start] <= target { 1.0 } else { -1.0 };
+
+    (start..end).fold(0.0, |maximum: f64, i| maximum.max((means[i] - target) * direction))
+}
+
+fn measure_trait(label: &str, means: &Vec<f64>, targets: &Vec<u32>, criteria: Criteria) -> TraitConvergence {
+    let length = means.len().min(targets.len());
+
+    let mut boundaries = (1..length).filter(|&i| targets[i] != targets[i - 1]).collect::<Vec<usize>>();
+
+    boundaries.insert(0, 0);
+    boundaries.push(length);
+
+    let segments = boundaries.windows(2).map(|bounds| (bounds[0], bounds[1])).collect::<Vec<(usize, usize)>>();
+
+    let adaptations = segments.iter().skip(1).map(|&(start, end)| {
+        Adaptation {
+            generation: start as u32,
+            from:       targets[start - 1],
+            to:         targets[start],
+            lag:        settle(&means, &targets, start, end, criteria).map(|settled| (settled - start) as u32),
+            overshoot:  overshoot(&means, &targets, start, end)
+        }
+    }).collect::<Vec<Adaptation>>();
+
+    let (first_start, first_end) = segments[0];
+    let (last_start, _)          = segments[segments.len() - 1];
+
+    let converged = settle(&means, &targets, first_start, first_end, criteria);
+
+    let largest_overshoot = segments.iter().fold(0.0, |maximum: f64, &(start, end)| {
+        maximum.max(overshoot(&means, &targets, start, end))
+    });
+
+    // The mean error over the final window, within the final target.
+    let steady_start = last_start.max(length.saturating_sub(criteria.window));
+    let steady_state = (steady_start..length).fold(0.0, |acc, i| acc + means[i] - targets[i] as f64);
+
+    TraitConvergence {
+        name:                       label.to_string(),
+        generations_to_convergence: converged.map(|settled| settled as u32),
+        overshoot:                  largest_overshoot,
+        steady_state_error:         steady_state / (length - steady_start).max(1) as f64,
+        adaptations:                adaptations
+    }
+}
+
+// Whether every trait mean has stayed within tolerance for the last window
+// generations.
+pub fn has_converged(statistics: &Statistics, criteria: Criteria) -> bool {
+    statistics.trait_histories().into_iter().all(|(_, means, targets)| {
+        means.len() >= criteria.window && (means.len() - criteria.window..means.len()).all(|i| {
+            is_within(means[i], targets[i], criteria.tolerance)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRITERIA: Criteria = Criteria { tolerance: 0.1, window: 3 };
+
+    #[test]
+    fn a_full_window_settles() {
+        let means   = vec![50.0, 95.0, 100.0, 105.0, 100.0];
+        let targets = vec![100; 5];
+
+        assert_eq!(settle(&means, &targets, 0, 5, CRITERIA), Some(1));
+    }
+
+    #[test]
+    fn a_streak_cut_short_by_a_target_change_settles() {
+        let means   = vec![50.0, 100.0, 100.0, 100.0, 100.0];
+        let targets = vec![100, 100, 100, 200, 200];
+
+        assert_eq!(settle(&means, &targets, 0, 3, CRITERIA), Some(1));
+    }
+
+    #[test]
+    fn a_streak_cut_short_by_the_end_of_the_run_does_not_settle() {
+        let means   = vec![50.0, 50.0, 50.0, 100.0, 100.0];
+        let targets = vec![100; 5];
+
+        assert_eq!(settle(&means, &targets, 0, 5, CRITERIA), None);
+    }
+
+    #[test]
+    fn leaving_tolerance_restarts_the_streak() {
+        let means   = vec![100.0, 100.0, 50.0, 100.0, 100.0, 100.0];
+        let targets = vec![100; 6];
+
+        assert_eq!(settle(&means, &targets, 0, 6, CRITERIA), Some(3));
+    }
+}
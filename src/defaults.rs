@@ -1,11 +1,38 @@
 use blending::BlendingMode;
+use canopy::ForageModel;
+use competition::Competition;
+use mating::{Inbreeding, Mating, MatingTrait};
 use mutation::MutationDecay;
+use output::ImageFormat;
+use selection::Selection;
 
-pub const SIMULATION_LENGTH: u32           = 1500;
-pub const WORLD_SIZE:        u16           = 1000;
-pub const TREE_HEIGHT:       u32           = 1500;
-pub const MUTATION_RATE:     f32           = 0.001;
-pub const LION_SPEED:        u32           = 500;
-pub const COLOR:             u32           = 500;
-pub const BLENDING_MODE:     BlendingMode  = BlendingMode::OnePointCrossover;
-pub const MUTATION_DECAY:    MutationDecay = MutationDecay::None;
+pub const SIMULATION_LENGTH:     u32           = 1500;
+pub const WORLD_SIZE:            u16           = 1000;
+pub const TREE_HEIGHT:           u32           = 1500;
+pub const MUTATION_RATE:         f32           = 0.001;
+pub const LION_SPEED:            u32           = 500;
+pub const COLOR:                 u32           = 500;
+pub const BLENDING_MODE:         BlendingMode  = BlendingMode::OnePointCrossover;
+pub const MUTATION_DECAY:        MutationDecay = MutationDecay::None;
+pub const FORAGE_MODEL:          ForageModel   = ForageModel::TreeHeight;
+pub const COMPETITION:           Competition   = Competition::None;
+pub const COMPETITION_STRENGTH:  f32           = 1.0;
+pub const SELECTION:             Selection     = Selection::Natural;
+pub const MARKERS:               usize         = 0;
+pub const MATING:                Mating        = Mating::Random;
+pub const MATING_TRAIT:          MatingTrait   = MatingTrait::Color;
+pub const MATE_CANDIDATES:       u32           = 5;
+pub const INBREEDING:            Inbreeding    = Inbreeding::Allow;
+pub const TERRAIN_RESOLUTION:    usize         = 256;
+pub const OUTPUT_DIRECTORY:      &'static str  = ".";
+pub const IMAGE_FORMAT:          ImageFormat   = ImageFormat::Png;
+pub const IMAGE_WIDTH:           u32           = 640;
+pub const IMAGE_HEIGHT:          u32           = 480;
+pub const SWEEP_REPLICATES:      u32           = 5;
+pub const CONVERGENCE_TOLERANCE: f64           = 0.05;
+pub const CONVERGENCE_WINDOW:    usize         = 50;
+pub const GENEALOGY_SAMPLE:      usize         = 16;
+pub const GENEALOGY_DEPTH:       u32           = 8;
+pub const SHIFT_WINDOW:          usize         = 50;
+pub const SHIFT_THRESHOLD:       f64           = 3.0;
+pub const BOXPLOT_INTERVAL:      u32           = 50;
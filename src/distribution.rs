@@ -0,0 +1,132 @@
+use giraffe_lib::quantile;
+
+
+// The bimodality coefficient of a uniform distribution. Higher values
+// suggest two or more modes.
+pub const BIMODALITY_THRESHOLD: f64 = 5.0 / 9.0;
+
+// The shape of a trait's values in one generation. Skewness and kurtosis are
+// the sample-size corrected estimates, kurtosis in excess of the normal.
+#[derive(Clone, Debug)]
+pub struct Distribution {
+    pub minimum:        f64,
+    pub lower_quartile: f64,
+    pub median:         f64,
+    pub upper_quartile: f64,
+    pub maximum:        f64,
+    pub skewness:       f64,
+    pub kurtosis:       f64,
+    pub bimodality:     f64
+}
+
+impl Distribution {
+    pub fn new(values: &Vec<f64>) -> Self {
+        let mut sorted = values.clone();
+
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let (skewness, kurtosis, bimodality) = shape(&sorted);
+
+        Distribution {
+            minimum:        sorted.first().cloned().unwrap_or(0.0),
+            lower_quartile: quantile(&sorted, 0.25),
+            median:         quantile(&sorted, 0.5),
+            upper_quartile: quantile(&sorted, 0.75),
+            maximum:        sorted.last().cloned().unwrap_or(0.0),
+            skewness:       skewness,
+            kurtosis:       kurtosis,
+            bimodality:     bimodality
+        }
+    }
+
+    pub fn is_bimodal(&self) -> bool {
+        self.bimodality > BIMODALITY_THRESHOLD
+    }
+}
+
+// Skewness, excess kurtosis and the bimodality coefficient
+// (skewness² + 1) / (kurtosis + 3(n - 1)² / ((n - 2)(n - 3))). A herd with
+// no spread, or too few giraffes to correct for, has all three at zero.
+fn shape(values: &Vec<f64>) -> (f64, f64, f64) {
+    let n = values.len() as f64;
+
+    if n < 4.0 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let mean = values.iter().fold(0.0, |acc, value| acc + value) / n;
+
+    let (m2, m3, m4) = values.iter().fold((0.0, 0.0, 0.0), |(m2, m3, m4), value| {
+        let deviation = value - mean;
+
+        (m2 + deviation.powi(2) / n, m3 + deviation.powi(3) / n, m4 + deviation.powi(4) / n)
+    });
+
+    if m2 <= 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let g1 = m3 / m2.powf(1.5);
+    let g2 = m4 / m2.powi(2) - 3.0;
+
+    let skewness = (n * (n - 1.0)).sqrt() / (n - 2.0) * g1;
+    let kurtosis = (n - 1.0) / ((n - 2.0) * (n - 3.0)) * ((n + 1.0) * g2 + 6.0);
+
+    let bimodality = (skewness.powi(2) + 1.0) / (kurtosis + 3.0 * (n - 1.0).powi(2) / ((n - 2.0) * (n - 3.0)));
+
+    (skewness, kurtosis, bimodality)
+}
+
+#[cfg(test)]
+mod tests {
+    use statistical;
+
+    use super::*;
+
+    const EPSILON: f64 = 1e-9;
+
+    #[test]
+    fn a_symmetric_sample_has_no_skew() {
+        let distribution = Distribution::new(&vec![1.0, 2.0, 4.0, 5.0, 6.0, 8.0, 9.0]);
+
+        assert!(distribution.skewness.abs() < EPSILON);
+        assert_eq!(distribution.median, 5.0);
+    }
+
+    #[test]
+    fn two_separate_points_are_bimodal() {
+        let mut values = vec![0.0; 20];
+
+        values.extend(vec![10.0; 20]);
+
+        let distribution = Distribution::new(&values);
+
+        assert!(distribution.bimodality > BIMODALITY_THRESHOLD);
+        assert!(distribution.is_bimodal());
+    }
+
+    #[test]
+    fn a_normal_looking_sample_is_not_bimodal() {
+        let distribution = Distribution::new(&vec![1.0, 4.0, 5.0, 5.0, 5.0, 5.0, 6.0, 9.0]);
+
+        assert!(!distribution.is_bimodal());
+    }
+
+    #[test]
+    fn shape_matches_the_statistical_crate() {
+        let values       = vec![2.0, 3.5, 3.0, 8.0, 1.0, 4.5, 12.0, 3.0, 2.5, 6.0];
+        let distribution = Distribution::new(&values);
+
+        assert!((distribution.skewness - statistical::univariate::skewness(&values, None, None)).abs() < EPSILON);
+        assert!((distribution.kurtosis - statistical::univariate::kurtosis(&values, None, None)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn too_few_or_identical_values_have_no_shape() {
+        let few       = Distribution::new(&vec![1.0, 2.0, 9.0]);
+        let identical = Distribution::new(&vec![4.0; 6]);
+
+        assert_eq!((few.skewness, few.kurtosis, few.bimodality), (0.0, 0.0, 0.0));
+        assert_eq!((identical.skewness, identical.kurtosis, identical.bimodality), (0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use gnuplot;
+use gnuplot::AxesCommon;
+
+use blending::BlendingMode;
+use mutation;
+use options::Options;
+use statistics::Statistics;
+
+
+// Every mutation draws one of this many alleles.
+const ALLELES: f64 = 256.0;
+
+// Mean blending averages both parents' genes instead of copying one, so the
+// expectation below doesn't hold for it.
+pub fn applies_to(blending_mode: BlendingMode) -> bool {
+    match blending_mode {
+        BlendingMode::Mean => false,
+        _                  => true
+    }
+}
+
+// Observed heterozygosity against the Wright–Fisher expectation for the herd
+// size. Each gene a child inherits is a copy of one gene in the generation
+// before, so with crossover the herd behaves as N haploid genes per locus and
+// heterozygosity decays as H_t = H_0 (1 - 1/N)^t. The mutation expectation
+// adds the chance that either of two genes was replaced, after which they
+// match one time in 256.
+pub struct Drift {
+    pub loci:          &'static str,
+    pub observed:      Vec<f64>,
+    pub expected:      Vec<f64>,
+    pub with_mutation: Vec<f64>
+}
+
+impl Drift {
+    // Follows the neutral markers when the giraffes carry them, otherwise
+    // the trait loci, which are only neutral under neutral selection.
+    pub fn new(statistics: &Statistics, options: &Options) -> Self {
+        let (loci, observed) = if statistics.marker_heterozygosities().is_empty() {
+            ("trait loci", statistics.heterozygosities().clone())
+        } else {
+            ("markers", statistics.marker_heterozygosities().clone())
+        };
+
+        let initial   = observed.first().cloned().unwrap_or(0.0);
+        let herd_size = options.herd_size as f64;
+
+        let mut expected      = vec![initial];
+        let mut with_mutation = vec![initial];
+
+        for generation in 1..observed.len() {
+            let rate = mutation::calculate_mutation_rate(
+                options.mutation_decay,
+                options.mutation_rate,
+                generation as u32,
+                options.simulation_length
+            );
+
+            let kept      = (1.0 - mutation::mutation_probability(rate)).powi(2);
+            let sampled   = with_mutation[generation - 1] * (1.0 - 1.0 / herd_size);
+            let identical = kept * (1.0 - sampled) + (1.0 - kept) / ALLELES;
+
+            expected.push(expected[generation - 1] * (1.0 - 1.0 / herd_size));
+            with_mutation.push(1.0 - identical);
+        }
+
+        Drift {
+            loci:          loci,
+            observed:      observed,
+            expected:      expected,
+            with_mutation: with_mutation
+        }
+    }
+
+    pub fn print_summary<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if let (Some(observed), Some(expected), Some(with_mutation)) =
+            (self.observed.last(), self.expected.last(), self.with_mutation.last()) {
+            writeln!(
+                out,
+                "{} heterozygosity: initial: {:.3}, final: {:.3}, Wright-Fisher expectation: {:.3}, with mutation: {:.3}",
+                self.loci,
+                self.observed[0],
+                observed,
+                expected,
+                with_mutation
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn export_csv(&self, destination_file: &str) -> io::Result<()> {
+        let mut file = File::create(destination_file)?;
+
+        writeln!(file, "generation,heterozygosity,wright_fisher,wright_fisher_with_mutation")?;
+
+        for i in 0..self.observed.len() {
+            writeln!(file, "{},{},{},{}", i, self.observed[i], self.expected[i], self.with_mutation[i])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_figure(&self, terminal: &str, destination_file: &str) {
+        let x: Vec<f64> = (0..self.observed.len()).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+        figure.axes2d()
+            .set_x_label("Generation", &vec![])
+            .set_y_label(&format!("Heterozygosity of {}", self.loci), &vec![])
+            .lines(
+                &x,
+                &self.expected,
+                &[gnuplot::Caption("Wright-Fisher Drift"), gnuplot::Color("red"), gnuplot::LineStyle(gnuplot::Dash)]
+            )
+            .lines(
+                &x,
+                &self.with_mutation,
+                &[gnuplot::Caption("Wright-Fisher Drift With Mutation"), gnuplot::Color("red")]
+            )
+            .lines(
+                &x,
+                &self.observed,
+                &[gnuplot::Caption("Observed"), gnuplot::Color("black")]
+            );
+
+        figure.show();
+    }
+}
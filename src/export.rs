@@ -0,0 +1,218 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+
+use nalgebra;
+
+use options::Options;
+use solution_space;
+use terrain;
+use terrain::TerrainSpec;
+
+
+const NPY_MAGIC:     &'static [u8] = b"\x93NUMPY";
+const NPY_ALIGNMENT: usize         = 64;
+const STL_HEADER:    usize         = 80;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Format {
+    Obj,
+    Ply,
+    Stl,
+    Csv,
+    Npy
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            Format::Obj => "obj",
+            Format::Ply => "ply",
+            Format::Stl => "stl",
+            Format::Csv => "csv",
+            Format::Npy => "npy"
+        }
+    }
+}
+
+// The mesh formats use the same coordinates as the kiss3d terrain: one unit per
+// 100 of each trait, with the y axis trait first, and fitness scaled by
+// solution_space::HEIGHT_SCALE. The grid formats hold the raw fitness values.
+pub fn export(destination: &str, format: Format, spec: &TerrainSpec, options: &Options) -> io::Result<()> {
+    let matrix   = terrain::create_fitness_matrix(&spec, &options);
+    let mut file = BufWriter::new(File::create(destination)?);
+
+    match format {
+        Format::Obj => write_obj(&mut file, &spec, &matrix),
+        Format::Ply => write_ply(&mut file, &spec, &matrix),
+        Format::Stl => write_stl(&mut file, &spec, &matrix),
+        Format::Csv => write_csv(&mut file, &spec, &matrix),
+        Format::Npy => write_npy(&mut file, &spec, &matrix)
+    }
+}
+
+fn describe(spec: &TerrainSpec) -> String {
+    format!(
+        "Fitness terrain: x = {} / 100, y = {} / 100, z = fitness * {}",
+        spec.y_axis.phenotype.label(),
+        spec.x_axis.phenotype.label(),
+        solution_space::HEIGHT_SCALE
+    )
+}
+
+fn vertex_colors(matrix: &Vec<Vec<f32>>) -> Vec<(u8, u8, u8)> {
+    let fitnesses         = matrix.concat();
+    let (minimum, spread) = solution_space::fitness_range(&fitnesses);
+
+    fitnesses.iter().map(|&fitness| {
+        let (r, g, b) = solution_space::gradient_color((fitness - minimum) / spread);
+
+        ((r * 255.0).round() as u8, (g * 255.0).round() as u8, (b * 255.0).round() as u8)
+    }).collect::<Vec<(u8, u8, u8)>>()
+}
+
+// Vertex colors follow the common `v x y z r g b` extension.
+fn write_obj<W: Write>(file: &mut W, spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> io::Result<()> {
+    let vertices = solution_space::create_mesh_points(&spec, &matrix);
+    let colors   = vertex_colors(&matrix);
+
+    writeln!(file, "# {}", describe(&spec))?;
+
+    for (vertex, &(r, g, b)) in vertices.iter().zip(colors.iter()) {
+        writeln!(
+            file, "v {} {} {} {:.4} {:.4} {:.4}",
+            vertex.x, vertex.y, vertex.z, r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0
+        )?;
+    }
+
+    for triangle in solution_space::create_mesh_triangles(&spec) {
+        writeln!(file, "f {} {} {}", triangle.x + 1, triangle.y + 1, triangle.z + 1)?;
+    }
+
+    Ok(())
+}
+
+fn write_ply<W: Write>(file: &mut W, spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> io::Result<()> {
+    let vertices  = solution_space::create_mesh_points(&spec, &matrix);
+    let triangles = solution_space::create_mesh_triangles(&spec);
+    let colors    = vertex_colors(&matrix);
+    let fitnesses = matrix.concat();
+
+    writeln!(file, "ply")?;
+    writeln!(file, "format ascii 1.0")?;
+    writeln!(file, "comment {}", describe(&spec))?;
+    writeln!(file, "element vertex {}", vertices.len())?;
+    writeln!(file, "property float x")?;
+    writeln!(file, "property float y")?;
+    writeln!(file, "property float z")?;
+    writeln!(file, "property uchar red")?;
+    writeln!(file, "property uchar green")?;
+    writeln!(file, "property uchar blue")?;
+    writeln!(file, "property float fitness")?;
+    writeln!(file, "element face {}", triangles.len())?;
+    writeln!(file, "property list uchar uint vertex_indices")?;
+    writeln!(file, "end_header")?;
+
+    for ((vertex, &(r, g, b)), fitness) in vertices.iter().zip(colors.iter()).zip(fitnesses.iter()) {
+        writeln!(file, "{} {} {} {} {} {} {}", vertex.x, vertex.y, vertex.z, r, g, b, fitness)?;
+    }
+
+    for triangle in triangles {
+        writeln!(file, "3 {} {} {}", triangle.x, triangle.y, triangle.z)?;
+    }
+
+    Ok(())
+}
+
+fn write_f32s<W: Write>(file: &mut W, values: &[f32]) -> io::Result<()> {
+    for value in values {
+        file.write_all(&value.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn normal(a: &nalgebra::Point3<f32>, b: &nalgebra::Point3<f32>, c: &nalgebra::Point3<f32>) -> [f32; 3] {
+    let (ux, uy, uz) = (b.x - a.x, b.y - a.y, b.z - a.z);
+    let (vx, vy, vz) = (c.x - a.x, c.y - a.y, c.z - a.z);
+
+    let (nx, ny, nz) = (uy * vz - uz * vy, uz * vx - ux * vz, ux * vy - uy * vx);
+    let length       = (nx * nx + ny * ny + nz * nz).sqrt().max(::std::f32::EPSILON);
+
+    [nx / length, ny / length, nz / length]
+}
+
+// Binary STL has no vertex attributes, so each facet carries the mean color of
+// its corners in the VisCAM/SolidView 15-bit attribute.
+fn write_stl<W: Write>(file: &mut W, spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> io::Result<()> {
+    let vertices  = solution_space::create_mesh_points(&spec, &matrix);
+    let triangles = solution_space::create_mesh_triangles(&spec);
+    let colors    = vertex_colors(&matrix);
+
+    let mut header = describe(&spec).into_bytes();
+
+    header.resize(STL_HEADER, b' ');
+
+    file.write_all(&header)?;
+    file.write_all(&(triangles.len() as u32).to_le_bytes())?;
+
+    for triangle in triangles {
+        let corners = [triangle.x as usize, triangle.y as usize, triangle.z as usize];
+        let (a, b, c) = (&vertices[corners[0]], &vertices[corners[1]], &vertices[corners[2]]);
+
+        let (red, green, blue) = corners.iter().fold((0u16, 0u16, 0u16), |(red, green, blue), &corner| {
+            let (r, g, b) = colors[corner];
+
+            (red + r as u16, green + g as u16, blue + b as u16)
+        });
+
+        let attribute: u16 = 0x8000 | (red / 3 >> 3) << 10 | (green / 3 >> 3) << 5 | (blue / 3 >> 3);
+
+        write_f32s(file, &normal(a, b, c))?;
+        write_f32s(file, &[a.x, a.y, a.z, b.x, b.y, b.z, c.x, c.y, c.z])?;
+        file.write_all(&attribute.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn write_csv<W: Write>(file: &mut W, spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> io::Result<()> {
+    let x_values = spec.x_values();
+    let y_values = spec.y_values();
+
+    writeln!(file, "{},{},fitness", spec.x_axis.phenotype.name(), spec.y_axis.phenotype.name())?;
+
+    for (row, y) in matrix.iter().zip(y_values.iter()) {
+        for (fitness, x) in row.iter().zip(x_values.iter()) {
+            writeln!(file, "{},{},{}", x, y, fitness)?;
+        }
+    }
+
+    Ok(())
+}
+
+// A version 1.0 .npy file of shape (rows, cols) with rows following the y axis.
+fn write_npy<W: Write>(file: &mut W, spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> io::Result<()> {
+    let mut header = format!(
+        "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+        spec.resolution,
+        spec.resolution
+    );
+
+    let unpadded = NPY_MAGIC.len() + 2 + 2 + header.len() + 1;
+    let padding  = (NPY_ALIGNMENT - unpadded % NPY_ALIGNMENT) % NPY_ALIGNMENT;
+
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    file.write_all(NPY_MAGIC)?;
+    file.write_all(&[1, 0])?;
+    file.write_all(&(header.len() as u16).to_le_bytes())?;
+    file.write_all(header.as_bytes())?;
+
+    for row in matrix {
+        write_f32s(file, &row)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use gnuplot;
+use gnuplot::AxesCommon;
+
+use world::World;
+
+
+// How far back the final generation's lines of descent meet, written to
+// genealogy.json.
+#[derive(RustcEncodable)]
+pub struct Coalescence {
+    pub generations:               u32,
+    pub sample:                    Vec<u64>,
+    pub tmrca:                     Option<u32>,
+    pub sample_tmrca:              Option<u32>,
+    pub mean_pairwise_coalescence: Option<f64>,
+    pub uncoalesced_pairs:         usize,
+    pub founder_lineages:          usize,
+    pub founders_represented:      usize
+}
+
+// Every generation's parent ids, kept as indices into the generation before,
+// along with how many founders still have descendants. Founder lineages
+// follow the first parent only, so a selective sweep shows as a collapse to
+// a single lineage; founders represented follows both parents.
+pub struct Pedigree {
+    first_ids:            Vec<u64>,
+    parents:              Vec<Vec<(u32, u32)>>,
+    founders:             Vec<u32>,
+    ancestry:             Vec<Vec<u64>>,
+    founder_lineages:     Vec<usize>,
+    founders_represented: Vec<usize>,
+    distinct_parents:     Vec<usize>
+}
+
+impl Pedigree {
+    pub fn new() -> Self {
+        Pedigree {
+            first_ids:            vec![],
+            parents:              vec![],
+            founders:             vec![],
+            ancestry:             vec![],
+            founder_lineages:     vec![],
+            founders_represented: vec![],
+            distinct_parents:     vec![]
+        }
+    }
+
+    pub fn record(&mut self, world: &World) {
+        let first_id = world.tower.first().map_or(0, |giraffe| giraffe.id);
+
+        let (founders, ancestry, parents) = match self.first_ids.last() {
+            None => {
+                let words = (world.tower.len() + 63) / 64;
+
+                let ancestry = (0..world.tower.len()).map(|i| {
+                    let mut bits = vec![0; words];
+
+                    bits[i / 64] |= 1 << (i % 64);
+                    bits
+                }).collect::<Vec<Vec<u64>>>();
+
+                ((0..world.tower.len() as u32).collect(), ancestry, vec![])
+            },
+            Some(&previous_first_id) => {
+                let parents = world.tower.iter().map(|giraffe| {
+                    let (parent1, parent2) = giraffe.parent_ids.expect("Giraffe born without parents");
+
+                    ((parent1 - previous_first_id) as u32, (parent2 - previous_first_id) as u32)
+                }).collect::<Vec<(u32, u32)>>();
+
+                let founders = parents.iter().map(|&(parent1, _)| {
+                    self.founders[parent1 as usize]
+                }).collect::<Vec<u32>>();
+
+                let ancestry = parents.iter().map(|&(parent1, parent2)| {
+                    self.ancestry[parent1 as usize].iter().zip(&self.ancestry[parent2 as usize]).map(|(a, b)| {
+                        a | b
+                    }).collect()
+                }).collect::<Vec<Vec<u64>>>();
+
+                (founders, ancestry, parents)
+            }
+        };
+
+        let represented = ancestry.iter().fold(vec![0; ancestry.first().map_or(0, |bits| bits.len())], |acc, bits| {
+            acc.iter().zip(bits).map(|(a, b)| a | b).collect::<Vec<u64>>()
+        });
+
+        self.founder_lineages.push(founders.iter().collect::<HashSet<&u32>>().len());
+        self.founders_represented.push(represented.iter().map(|bits| bits.count_ones() as usize).sum());
+        self.distinct_parents.push(parents.iter().flat_map(|&(parent1, parent2)| {
+            vec![parent1, parent2]
+        }).collect::<HashSet<u32>>().len());
+
+        self.first_ids.push(first_id);
+        self.parents.push(parents);
+        self.founders = founders;
+        self.ancestry = ancestry;
+    }
+
+    fn latest_generation(&self) -> usize {
+        self.first_ids.len() - 1
+    }
+
+    fn id(&self, generation: usize, index: u32) -> u64 {
+        self.first_ids[generation] + index as u64
+    }
+
+    // Evenly spaced members of the final generation.
+    pub fn sample(&self, size: usize) -> Vec<u32> {
+        let herd = self.founders.len();
+        let size = size.min(herd);
+
+        (0..size).map(|i| (i * herd / size) as u32).collect()
+    }
+
+    // The first parent line of a giraffe in the final generation, from the
+    // giraffe itself back to its founder.
+    fn maternal_line(&self, index: u32) -> Vec<u32> {
+        let mut line = vec![index];
+
+        for generation in (1..self.first_ids.len()).rev() {
+            let current = line[line.len() - 1];
+            line.push(self.parents[generation][current as usize].0);
+        }
+
+        line
+    }
+
+    // Generations back to the most recent common first parent line ancestor
+    // of the given final generation giraffes, or None when they trace back to
+    // different founders.
+    fn tmrca(&self, indices: &Vec<u32>) -> Option<u32> {
+        let mut lineages = indices.iter().cloned().collect::<HashSet<u32>>();
+        let mut back     = 0;
+
+        for generation in (1..self.first_ids.len()).rev() {
+            if lineages.len() <= 1 {
+                break;
+            }
+
+            lineages = lineages.iter().map(|&index| self.parents[generation][index as usize].0).collect();
+            back    += 1;
+        }
+
+        if lineages.len() <= 1 { Some(back) } else { None }
+    }
+
+    pub fn coalescence(&self, sample: &Vec<u32>) -> Coalescence {
+        let latest = self.latest_generation();
+        let lines  = sample.iter().map(|&index| self.maternal_line(index)).collect::<Vec<Vec<u32>>>();
+
+        let mut times       = vec![];
+        let mut uncoalesced = 0;
+
+        for i in 0..lines.len() {
+            for j in i + 1..lines.len() {
+                match (0..lines[i].len()).find(|&back| lines[i][back] == lines[j][back]) {
+                    Some(back) => times.push(back as f64),
+                    None       => uncoalesced += 1
+                }
+            }
+        }
+
+        let mean_pairwise = if times.is_empty() {
+            None
+        } else {
+            Some(times.iter().fold(0.0, |acc, time| acc + time) / times.len() as f64)
+        };
+
+        Coalescence {
+            generations:               latest as u32,
+            sample:                    sample.iter().map(|&index| self.id(latest, index)).collect(),
+            tmrca:                     self.tmrca(&(0..self.founders.len() as u32).collect()),
+            sample_tmrca:              self.tmrca(&sample),
+            mean_pairwise_coalescence: mean_pairwise,
+            uncoalesced_pairs:         uncoalesced,
+            founder_lineages:          self.founder_lineages[latest],
+            founders_represented:      self.founders_represented[latest]
+        }
+    }
+
+    // The first parent tree of the sample in Newick format, leaves and
+    // ancestors labelled by id with branch lengths in generations. Lines that
+    // never meet are joined at an unlabelled root before the founders.
+    pub fn export_newick(&self, destination_file: &str, sample: &Vec<u32>) -> io::Result<()> {
+        let latest = self.latest_generation();
+
+        // Subtrees keyed by the index of their root's lineage in the current
+        // generation, with the generation their root was born in.
+        let mut subtrees = BTreeMap::new();
+
+        for &index in sample {
+            subtrees.entry(index).or_insert(vec![]).push((format!("g{}", self.id(latest, index)), latest));
+        }
+
+        let join = |children: Vec<(String, usize)>, generation: usize| {
+            children.into_iter().map(|(tree, born)| {
+                format!("{}:{}", tree, born - generation)
+            }).collect::<Vec<String>>().join(",")
+        };
+
+        for generation in (1..latest + 1).rev() {
+            let mut parents = BTreeMap::new();
+
+            for (index, children) in subtrees {
+                let parent = self.parents[generation][index as usize].0;
+                parents.entry(parent).or_insert(vec![]).extend(children);
+            }
+
+            subtrees = parents.into_iter().map(|(parent, children): (u32, Vec<(String, usize)>)| {
+                if children.len() > 1 {
+                    let tree = format!("({})g{}", join(children, generation - 1), self.id(generation - 1, parent));
+                    (parent, vec![(tree, generation - 1)])
+                } else {
+                    (parent, children)
+                }
+            }).collect();
+        }
+
+        let mut roots = subtrees.into_iter().flat_map(|(_, children)| children).collect::<Vec<(String, usize)>>();
+
+        let tree = if roots.len() == 1 {
+            roots.remove(0).0
+        } else {
+            format!("({})", join(roots, 0))
+        };
+
+        let mut file = File::create(destination_file)?;
+
+        writeln!(file, "{};", tree)
+    }
+
+    // Both parents of the sample, back depth generations, as a GraphViz
+    // digraph with one rank per generation.
+    pub fn export_dot(&self, destination_file: &str, sample: &Vec<u32>, depth: u32) -> io::Result<()> {
+        let latest   = self.latest_generation();
+        let earliest = latest.saturating_sub(depth as usize);
+
+        let mut file    = File::create(destination_file)?;
+        let mut members = sample.iter().cloned().collect::<HashSet<u32>>();
+
+        writeln!(file, "digraph genealogy {{")?;
+        writeln!(file, "    rankdir=TB;")?;
+        writeln!(file, "    node [shape=point];")?;
+
+        for generation in (earliest..latest + 1).rev() {
+            let mut sorted = members.iter().cloned().collect::<Vec<u32>>();
+
+            sorted.sort();
+
+            let names = sorted.iter().map(|&index| format!("g{}", self.id(generation, index))).collect::<Vec<String>>();
+
+            writeln!(file, "    {{ rank=same; {}; }}", names.join("; "))?;
+
+            if generation == earliest {
+                break;
+            }
+
+            let mut previous = HashSet::new();
+
+            for &index in &sorted {
+                let (parent1, parent2) = self.parents[generation][index as usize];
+
+                for &parent in if parent1 == parent2 { vec![parent1] } else { vec![parent1, parent2] }.iter() {
+                    writeln!(
+                        file,
+                        "    g{} -> g{};",
+                        self.id(generation - 1, parent),
+                        self.id(generation, index)
+                    )?;
+
+                    previous.insert(parent);
+                }
+            }
+
+            members = previous;
+        }
+
+        writeln!(file, "}}")
+    }
+
+    pub fn export_csv(&self, destination_file: &str) -> io::Result<()> {
+        let mut file = File::create(destination_file)?;
+
+        writeln!(file, "generation,founder_lineages,founders_represented,distinct_parents")?;
+
+        for i in 0..self.first_ids.len() {
+            writeln!(
+                file,
+                "{},{},{},{}",
+                i,
+                self.founder_lineages[i],
+                self.founders_represented[i],
+                self.distinct_parents[i]
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_lineages_figure(&self, terminal: &str, destination_file: &str) {
+        let x: Vec<f64> = (0..self.first_ids.len()).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+        figure.axes2d()
+            .set_x_label("Generation", &vec![])
+            .set_y_label("Founders", &vec![])
+            .lines(
+                &x,
+                &self.founders_represented,
+                &[gnuplot::Caption("Founders With Descendants"), gnuplot::Color("blue")]
+            )
+            .lines(
+                &x,
+                &self.founder_lineages,
+                &[gnuplot::Caption("Surviving First Parent Lineages"), gnuplot::Color("black")]
+            );
+
+        figure.show();
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+const ALLELES: usize = 256;
+
+pub fn allele_counts(genomes: &Vec<Vec<u8>>) -> Vec<Vec<u32>> {
+    let num_loci = genomes.first().map_or(0, |genome| genome.len());
+
+    let mut counts = vec![vec![0u32; ALLELES]; num_loci];
+
+    for genome in genomes.iter() {
+        for (locus, &allele) in genome.iter().enumerate() {
+            counts[locus][allele as usize] += 1;
+        }
+    }
+
+    counts
+}
+
+pub fn locus_entropies(allele_counts: &Vec<Vec<u32>>) -> Vec<f64> {
+    allele_counts.iter().map(|locus_counts| {
+        let total = locus_counts.iter().fold(0, |acc, &count| acc + count) as f64;
+
+        locus_counts.iter().filter(|&&count| count > 0).fold(0.0, |acc, &count| {
+            let p = count as f64 / total;
+
+            acc - p * p.log2()
+        })
+    }).collect::<Vec<f64>>()
+}
+
+// Summing the per-locus probability that two distinct genomes differ gives
+// the mean pairwise Hamming distance without comparing every pair.
+pub fn mean_pairwise_hamming_distance(allele_counts: &Vec<Vec<u32>>) -> f64 {
+    allele_counts.iter().map(|locus_counts| {
+        let total = locus_counts.iter().fold(0, |acc, &count| acc + count) as f64;
+
+        if total < 2.0 {
+            return 0.0;
+        }
+
+        let same_pairs = locus_counts.iter().fold(0.0, |acc, &count| {
+            acc + count as f64 * (count as f64 - 1.0)
+        });
+
+        1.0 - same_pairs / (total * (total - 1.0))
+    }).fold(0.0, |acc, distance| acc + distance)
+}
+
+// The chance two distinct genomes differ at a locus, averaged over loci.
+pub fn heterozygosity(allele_counts: &Vec<Vec<u32>>) -> f64 {
+    if allele_counts.is_empty() {
+        return 0.0;
+    }
+
+    mean_pairwise_hamming_distance(&allele_counts) / allele_counts.len() as f64
+}
+
+pub fn unique_genomes(genomes: &Vec<Vec<u8>>) -> usize {
+    genomes.iter().collect::<HashSet<&Vec<u8>>>().len()
+}
+
+// Each child takes two parent slots, drawn in proportion to fitness, which
+// gives the variance in family size needed by Ne = (4N - 2) / (Vk + 2).
+pub fn effective_population_size(fitnesses: &Vec<f32>) -> f64 {
+    let n     = fitnesses.len() as f64;
+    let total = fitnesses.iter().fold(0.0, |acc, &fitness| acc + fitness as f64);
+
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let variance = fitnesses.iter().fold(0.0, |acc, &fitness| {
+        let expected_offspring = 2.0 * n * fitness as f64 / total;
+
+        acc + (expected_offspring - 2.0).powi(2)
+    }) / n;
+
+    (4.0 * n - 2.0) / (variance + 2.0)
+}
+
+pub fn selection_differential(values: &Vec<f64>, fitnesses: &Vec<f32>) -> f64 {
+    let total = fitnesses.iter().fold(0.0, |acc, &fitness| acc + fitness as f64);
+
+    if values.is_empty() || total <= 0.0 {
+        return 0.0;
+    }
+
+    let mean          = values.iter().fold(0.0, |acc, value| acc + value) / values.len() as f64;
+    let selected_mean = values.iter().zip(fitnesses).fold(0.0, |acc, (value, &fitness)| {
+        acc + value * fitness as f64
+    }) / total;
+
+    selected_mean - mean
+}
@@ -1,6 +1,5 @@
-extern crate rand;
-
 use blending::{BlendingMode, blend_chromosomes};
+use giraffe_lib::random_u8;
 use mutation;
 
 use traits::CanMate;
@@ -14,30 +13,71 @@ const LEG_SEGMENTS:   usize = 4;
 const NECK_SEGMENTS:  usize = 8;
 const COLOR_SEGMENTS: usize = 4;
 
+// A preference is for a color, so it is carried on as many genes.
+const PREFERENCE_SEGMENTS: usize = COLOR_SEGMENTS;
+
+pub const MAX_LEG_LENGTH:  u32 = LEG_SEGMENTS as u32 * (!0 as u8) as u32;
+pub const MAX_NECK_LENGTH: u32 = NECK_SEGMENTS as u32 * (!0 as u8) as u32;
+pub const MAX_COLOR:       u32 = COLOR_SEGMENTS as u32 * (!0 as u8) as u32;
+
+pub const MAX_MARKERS: usize = 64;
+
+// The chromosomes in genome order, with their number of genes.
+pub const CHROMOSOMES: [(&'static str, usize); 3] = [
+    ("color", COLOR_SEGMENTS),
+    ("legs",  LEG_SEGMENTS),
+    ("neck",  NECK_SEGMENTS)
+];
+
+// The id and parent ids are assigned by the world a giraffe is born into;
+// founders have no parents. Markers and the mate preference are inherited
+// and mutated like the other chromosomes but never enter fitness.
 pub struct Giraffe {
-    color: Vec<u8>,
-    legs:  Vec<u8>,
-    neck:  Vec<u8>
+    pub id:         u64,
+    pub parent_ids: Option<(u64, u64)>,
+    color:          Vec<u8>,
+    legs:           Vec<u8>,
+    neck:           Vec<u8>,
+    markers:        Vec<u8>,
+    preference:     Vec<u8>
 }
 
 impl Giraffe {
     pub fn random() -> Self {
+        Giraffe::random_with(0, false)
+    }
+
+    pub fn random_with(markers: usize, has_preference: bool) -> Self {
         let color_chromosome: Vec<u8> = (0..COLOR_SEGMENTS).map(|_| {
-            rand::random::<u8>()
+            random_u8()
         }).collect();
 
         let legs_chromosome: Vec<u8> = (0..LEG_SEGMENTS).map(|_| {
-            rand::random::<u8>()
+            random_u8()
         }).collect();
 
         let neck_chromosome: Vec<u8> = (0..NECK_SEGMENTS).map(|_| {
-            rand::random::<u8>()
+            random_u8()
+        }).collect();
+
+        let marker_chromosome: Vec<u8> = (0..markers).map(|_| {
+            random_u8()
+        }).collect();
+
+        let preference_genes = if has_preference { PREFERENCE_SEGMENTS } else { 0 };
+
+        let preference_chromosome: Vec<u8> = (0..preference_genes).map(|_| {
+            random_u8()
         }).collect();
 
         Giraffe {
-            color: color_chromosome,
-            legs:  legs_chromosome,
-            neck:  neck_chromosome
+            id:         0,
+            parent_ids: None,
+            color:      color_chromosome,
+            legs:       legs_chromosome,
+            neck:       neck_chromosome,
+            markers:    marker_chromosome,
+            preference: preference_chromosome
         }
     }
 
@@ -91,9 +131,13 @@ impl Giraffe {
         }
 
         Giraffe {
-            color: color_chromosome,
-            legs:  legs_chromosome,
-            neck:  neck_chromosome
+            id:         0,
+            parent_ids: None,
+            color:      color_chromosome,
+            legs:       legs_chromosome,
+            neck:       neck_chromosome,
+            markers:    vec![],
+            preference: vec![]
         }
     }
 
@@ -108,6 +152,29 @@ impl Giraffe {
             acc + value as u32
         })
     }
+
+    pub fn markers(&self) -> &Vec<u8> {
+        &self.markers
+    }
+
+    // The color preferred in a mate, on the same scale as color.
+    pub fn preference(&self) -> u32 {
+        self.preference.iter().fold(0, |acc, &value| {
+            acc + value as u32
+        })
+    }
+
+    pub fn has_preference(&self) -> bool {
+        !self.preference.is_empty()
+    }
+
+    pub fn genome(&self) -> Vec<u8> {
+        self.color.iter()
+            .chain(self.legs.iter())
+            .chain(self.neck.iter())
+            .cloned()
+            .collect::<Vec<u8>>()
+    }
 }
 
 impl CanMutate for Giraffe {
@@ -124,10 +191,22 @@ impl CanMutate for Giraffe {
             &self.neck, mutation_rate
         );
 
+        let mutated_marker_chromosome = mutation::apply_mutations(
+            &self.markers, mutation_rate
+        );
+
+        let mutated_preference_chromosome = mutation::apply_mutations(
+            &self.preference, mutation_rate
+        );
+
         Giraffe {
-            color: mutated_color_chromosome,
-            legs:  mutated_legs_chromosome,
-            neck:  mutated_neck_chromosome
+            id:         self.id,
+            parent_ids: self.parent_ids,
+            color:      mutated_color_chromosome,
+            legs:       mutated_legs_chromosome,
+            neck:       mutated_neck_chromosome,
+            markers:    mutated_marker_chromosome,
+            preference: mutated_preference_chromosome
         }
     }
 }
@@ -138,9 +217,13 @@ impl CanMate for Giraffe {
         let mutated2 = giraffe2.mutate(mutation_rate);
 
         Giraffe {
-            color: blend_chromosomes(&mutated1.color, &mutated2.color, blending_mode),
-            legs:  blend_chromosomes(&mutated1.legs, &mutated2.legs, blending_mode),
-            neck:  blend_chromosomes(&mutated1.neck, &mutated2.neck, blending_mode)
+            id:         0,
+            parent_ids: Some((giraffe1.id, giraffe2.id)),
+            color:      blend_chromosomes(&mutated1.color, &mutated2.color, blending_mode),
+            legs:       blend_chromosomes(&mutated1.legs, &mutated2.legs, blending_mode),
+            neck:       blend_chromosomes(&mutated1.neck, &mutated2.neck, blending_mode),
+            markers:    blend_chromosomes(&mutated1.markers, &mutated2.markers, blending_mode),
+            preference: blend_chromosomes(&mutated1.preference, &mutated2.preference, blending_mode)
         }
     }
 }
@@ -161,16 +244,20 @@ impl HasHeight for Giraffe {
 
 impl HasSpeed for Giraffe {
     fn speed(&self) -> u32 {
-        let leg_length  = self.leg_length() as f32;
-        let neck_length = self.neck_length() as f32;
+        calculate_speed(self.leg_length(), self.neck_length())
+    }
+}
 
-        let weight = 4.0 * leg_length + neck_length;
-        let speed  = leg_length * 4.0 - 0.0001 * weight * weight;
+pub fn calculate_speed(leg_length: u32, neck_length: u32) -> u32 {
+    let leg_length  = leg_length as f32;
+    let neck_length = neck_length as f32;
 
-        if speed <= 0f32 {
-            0u32
-        } else {
-            speed as u32
-        }
+    let weight = 4.0 * leg_length + neck_length;
+    let speed  = leg_length * 4.0 - 0.0001 * weight * weight;
+
+    if speed <= 0f32 {
+        0u32
+    } else {
+        speed as u32
     }
 }
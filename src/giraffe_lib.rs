@@ -1,6 +1,48 @@
+use std::cell::Cell;
+
 use rand;
 
+// Every thread draws from its own splitmix64 generator, seeded at random
+// unless seed_random is called, so a seeded run can be repeated exactly.
+thread_local! {
+    static RANDOM_STATE: Cell<u64> = Cell::new(rand::random::<u64>());
+}
+
+pub fn seed_random(seed: u64) {
+    RANDOM_STATE.with(|state| state.set(seed));
+}
+
+pub fn random_u64() -> u64 {
+    RANDOM_STATE.with(|state| {
+        let next = state.get().wrapping_add(0x9e3779b97f4a7c15);
+
+        state.set(next);
+
+        let mut z = next;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    })
+}
+
+pub fn random_u8() -> u8 {
+    (random_u64() >> 56) as u8
+}
+
 pub fn random_proportion() -> f32 {
-    let result = (rand::random::<u8>() as f32) / ((!0 as u8) as f32);
+    let result = (random_u8() as f32) / ((!0 as u8) as f32);
     result
 }
+
+pub fn quantile(sorted_values: &Vec<f64>, q: f64) -> f64 {
+    if sorted_values.is_empty() {
+        return 0.0;
+    }
+
+    let position = q * (sorted_values.len() - 1) as f64;
+    let lower    = position.floor() as usize;
+    let upper    = position.ceil() as usize;
+    let weight   = position - lower as f64;
+
+    sorted_values[lower] * (1.0 - weight) + sorted_values[upper] * weight
+}
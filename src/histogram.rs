@@ -0,0 +1,39 @@
+#[derive(Copy, Clone, Debug)]
+pub struct Bins {
+    pub size:  u32,
+    pub count: usize
+}
+
+pub const COLOR_BINS:  Bins = Bins { size: 20, count: 52 };
+pub const HEIGHT_BINS: Bins = Bins { size: 60, count: 52 };
+pub const SPEED_BINS:  Bins = Bins { size: 50, count: 52 };
+pub const LEG_BINS:    Bins = Bins { size: 20, count: 52 };
+pub const NECK_BINS:   Bins = Bins { size: 40, count: 52 };
+
+pub fn histogram(values: &Vec<f64>, bins: Bins) -> Vec<u32> {
+    let mut counts = vec![0; bins.count];
+
+    for &value in values.iter() {
+        counts[bin_index(value, bins)] += 1;
+    }
+
+    counts
+}
+
+// Rows follow the y values and columns the x values, which is the layout
+// gnuplot expects for an image.
+pub fn density(x_values: &Vec<f64>, x_bins: Bins, y_values: &Vec<f64>, y_bins: Bins) -> Vec<u32> {
+    let mut counts = vec![0; x_bins.count * y_bins.count];
+
+    for (&x, &y) in x_values.iter().zip(y_values) {
+        counts[bin_index(y, y_bins) * x_bins.count + bin_index(x, x_bins)] += 1;
+    }
+
+    counts
+}
+
+fn bin_index(value: f64, bins: Bins) -> usize {
+    let index = (value.max(0.0) / bins.size as f64) as usize;
+
+    index.min(bins.count - 1)
+}
@@ -0,0 +1,314 @@
+use std::f64;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use gnuplot;
+use gnuplot::AxesCommon;
+
+use giraffe;
+use statistics;
+
+
+// Gene values are counted in this many equal bins for allele frequencies.
+pub const ALLELE_BINS: usize = 8;
+
+const LOCUS_COLORS: [&'static str; 8] = ["black", "red", "blue", "green", "orange", "purple", "brown", "cyan"];
+
+// Loci given a panel of allele frequencies, and how many of the furthest
+// moved stand in when none was flagged.
+const MAX_FREQUENCY_PANELS: usize = 6;
+const FALLBACK_LOCI:        usize = 3;
+
+// How far a locus mean moved after an environment change, against how far it
+// would be expected to wander in that time at the rate it moved before.
+pub struct LocusShift {
+    pub generation:  u32,
+    pub locus:       String,
+    pub before:      f64,
+    pub after:       f64,
+    pub expected:    f64,
+    pub score:       f64,
+    pub significant: bool
+}
+
+// Every generation's mean, standard deviation and binned allele frequencies
+// at each locus of the genome.
+pub struct LocusHistory {
+    means:          Vec<Vec<f64>>,
+    std_deviations: Vec<Vec<f64>>,
+    frequencies:    Vec<Vec<Vec<f64>>>
+}
+
+impl LocusHistory {
+    pub fn new() -> Self {
+        LocusHistory {
+            means:          vec![],
+            std_deviations: vec![],
+            frequencies:    vec![]
+        }
+    }
+
+    pub fn update(&mut self, allele_counts: &Vec<Vec<u32>>) {
+        let mut means          = vec![];
+        let mut std_deviations = vec![];
+        let mut frequencies    = vec![];
+
+        for counts in allele_counts {
+            let total = counts.iter().fold(0, |acc, &count| acc + count).max(1) as f64;
+
+            let mean = counts.iter().enumerate().fold(0.0, |acc, (allele, &count)| {
+                acc + allele as f64 * count as f64
+            }) / total;
+
+            let variance = counts.iter().enumerate().fold(0.0, |acc, (allele, &count)| {
+                acc + (allele as f64 - mean).powi(2) * count as f64
+            }) / total;
+
+            let bin_width = (counts.len() + ALLELE_BINS - 1) / ALLELE_BINS;
+
+            means.push(mean);
+            std_deviations.push(variance.sqrt());
+            frequencies.push(counts.chunks(bin_width).map(|bin| {
+                bin.iter().fold(0, |acc, &count| acc + count) as f64 / total
+            }).collect());
+        }
+
+        self.means.push(means);
+        self.std_deviations.push(std_deviations);
+        self.frequencies.push(frequencies);
+    }
+
+    // Compares each locus mean window generations after every environment
+    // change, or up to the next one, with its mean at the change. The
+    // expected movement is the root mean square change per generation over
+    // the window before, scaled by the square root of the generations since,
+    // as for a random walk. Loci that moved threshold times that are flagged.
+    pub fn shifts(&self, changes: &Vec<u32>, window: usize, threshold: f64) -> Vec<LocusShift> {
+        let names = locus_names();
+        let last  = self.means.len().saturating_sub(1);
+
+        let mut shifts = vec![];
+
+        for (i, &change) in changes.iter().enumerate() {
+            let change = change as usize;
+            let start  = change.saturating_sub(window);
+            let end    = changes.get(i + 1).map_or(last, |&next| next as usize).min(change + window).min(last);
+
+            if change - start < 2 || end <= change {
+                continue;
+            }
+
+            for (locus, name) in names.iter().enumerate() {
+                let steps = (start + 1..change).fold(0.0, |acc, g| {
+                    acc + (self.means[g][locus] - self.means[g - 1][locus]).powi(2)
+                });
+
+                let before   = self.means[change][locus];
+                let after    = self.means[end][locus];
+                let expected = (steps / (change - start - 1) as f64).sqrt() * ((end - change) as f64).sqrt();
+
+                let score = if expected > 0.0 {
+                    (after - before).abs() / expected
+                } else if after != before {
+                    f64::INFINITY
+                } else {
+                    0.0
+                };
+
+                shifts.push(LocusShift {
+                    generation:  change as u32,
+                    locus:       name.clone(),
+                    before:      before,
+                    after:       after,
+                    expected:    expected,
+                    score:       score,
+                    significant: score >= threshold
+                });
+            }
+        }
+
+        shifts
+    }
+
+    pub fn export_csv(&self, destination_file: &str) -> io::Result<()> {
+        let mut file = File::create(destination_file)?;
+
+        let mut header = vec!["generation".to_string()];
+
+        for name in locus_names() {
+            header.push(format!("{}_mean", name));
+            header.push(format!("{}_std_deviation", name));
+
+            for bin in 0..ALLELE_BINS {
+                header.push(format!("{}_bin_{}", name, bin));
+            }
+        }
+
+        writeln!(file, "{}", header.join(","))?;
+
+        for g in 0..self.means.len() {
+            let mut row = vec![g.to_string()];
+
+            for locus in 0..self.means[g].len() {
+                row.push(self.means[g][locus].to_string());
+                row.push(self.std_deviations[g][locus].to_string());
+                row.extend(self.frequencies[g][locus].iter().map(|frequency| frequency.to_string()));
+            }
+
+            writeln!(file, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+
+    // One panel per chromosome, a line per locus mean.
+    pub fn generate_figure(&self, terminal: &str, destination_file: &str, changes: &Vec<u32>) {
+        let x: Vec<f64> = (0..self.means.len()).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let mut figure = gnuplot::Figure::new();
+        let mut offset = 0;
+
+        figure.set_terminal(terminal, destination_file);
+
+        for (panel, &(chromosome, genes)) in giraffe::CHROMOSOMES.iter().enumerate() {
+            let axes = statistics::mark_environment_changes(figure.axes2d(), &changes)
+                .set_pos_grid(giraffe::CHROMOSOMES.len() as u32, 1, panel as u32)
+                .set_y_label(&format!("{} gene", chromosome), &vec![]);
+
+            if panel == giraffe::CHROMOSOMES.len() - 1 {
+                axes.set_x_label("Generation", &vec![]);
+            }
+
+            for gene in 0..genes {
+                let means = self.means.iter().map(|means| means[offset + gene]).collect::<Vec<f64>>();
+
+                axes.lines(
+                    &x,
+                    &means,
+                    &[
+                        gnuplot::Caption(&format!("{} {}", chromosome, gene)),
+                        gnuplot::Color(LOCUS_COLORS[gene % LOCUS_COLORS.len()])
+                    ]
+                );
+            }
+
+            offset += genes;
+        }
+
+        figure.show();
+    }
+
+    // A panel per locus with a line per allele bin's frequency. Loci flagged
+    // as shifted are plotted, or else the loci whose mean moved furthest over
+    // the run.
+    pub fn generate_frequency_figure(
+        &self,
+        terminal:         &str,
+        destination_file: &str,
+        shifts:           &Vec<LocusShift>,
+        changes:          &Vec<u32>
+    ) {
+        let names = locus_names();
+
+        let mut loci = vec![];
+
+        for shift in shifts.iter().filter(|shift| shift.significant) {
+            if let Some(locus) = names.iter().position(|name| *name == shift.locus) {
+                if !loci.contains(&locus) {
+                    loci.push(locus);
+                }
+            }
+        }
+
+        if loci.is_empty() {
+            loci = self.furthest_moved(FALLBACK_LOCI);
+        }
+
+        loci.truncate(MAX_FREQUENCY_PANELS);
+
+        let x: Vec<f64> = (0..self.frequencies.len()).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let bin_width = 256 / ALLELE_BINS;
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+
+        for (panel, &locus) in loci.iter().enumerate() {
+            let axes = statistics::mark_environment_changes(figure.axes2d(), &changes)
+                .set_pos_grid(loci.len() as u32, 1, panel as u32)
+                .set_y_label(&format!("{} frequency", names[locus]), &vec![])
+                .set_y_range(gnuplot::Fix(0.0), gnuplot::Fix(1.0));
+
+            if panel == loci.len() - 1 {
+                axes.set_x_label("Generation", &vec![]);
+            }
+
+            for bin in 0..ALLELE_BINS {
+                let frequencies = self.frequencies.iter().map(|frequencies| {
+                    frequencies[locus].get(bin).cloned().unwrap_or(0.0)
+                }).collect::<Vec<f64>>();
+
+                axes.lines(
+                    &x,
+                    &frequencies,
+                    &[
+                        gnuplot::Caption(&format!("{}-{}", bin * bin_width, (bin + 1) * bin_width - 1)),
+                        gnuplot::Color(LOCUS_COLORS[bin % LOCUS_COLORS.len()])
+                    ]
+                );
+            }
+        }
+
+        figure.show();
+    }
+
+    fn furthest_moved(&self, count: usize) -> Vec<usize> {
+        let (first, last) = match (self.means.first(), self.means.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _                         => return vec![]
+        };
+
+        let mut loci = (0..first.len()).collect::<Vec<usize>>();
+
+        loci.sort_by(|&a, &b| {
+            (last[b] - first[b]).abs().partial_cmp(&(last[a] - first[a]).abs()).unwrap()
+        });
+        loci.truncate(count);
+        loci
+    }
+}
+
+// Loci named after their chromosome and position, in genome order.
+pub fn locus_names() -> Vec<String> {
+    giraffe::CHROMOSOMES.iter().flat_map(|&(chromosome, genes)| {
+        (0..genes).map(move |gene| format!("{}_{}", chromosome, gene))
+    }).collect()
+}
+
+pub fn export_shifts_csv(destination_file: &str, shifts: &Vec<LocusShift>) -> io::Result<()> {
+    let mut file = File::create(destination_file)?;
+
+    writeln!(file, "generation,locus,before,after,expected,score,significant")?;
+
+    for shift in shifts {
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{}",
+            shift.generation,
+            shift.locus,
+            shift.before,
+            shift.after,
+            shift.expected,
+            shift.score,
+            shift.significant
+        )?;
+    }
+
+    Ok(())
+}
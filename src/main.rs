@@ -2,19 +2,57 @@ extern crate docopt;
 extern crate rustc_serialize;
 extern crate nalgebra;
 extern crate kiss3d;
+extern crate glfw;
 extern crate gnuplot;
 extern crate rand;
 extern crate statistical;
+extern crate toml;
 
+use std::fs::File;
+use std::io::Write;
+use std::iter;
+use std::process;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use rustc_serialize::json;
+
+use options::OptionsError;
+
+mod analysis;
+mod animation;
 mod blending;
+mod canopy;
+mod clustering;
+mod competition;
+mod config;
+mod convergence;
 mod defaults;
+mod distribution;
+mod drift;
+mod export;
+mod genealogy;
+mod genetics;
 mod mutation;
 mod giraffe;
 mod giraffe_lib;
+mod histogram;
+mod mating;
+mod loci;
 mod options;
+mod output;
+mod replicates;
+mod reporter;
+mod selection;
+mod simulation;
 mod solution_space;
 mod statistics;
+mod sweep;
+mod termination;
+mod terrain;
 mod traits;
+mod viewer;
 mod world;
 
 const USAGE: &'static str = "
@@ -22,110 +60,949 @@ Giraffe!
 
 Usage:
   giraffe [options]
-  giraffe terrain (plot|3d) [options]
+  giraffe terrain (plot|3d|contour|heatmap|analyze) [options]
+  giraffe terrain overlay (plot|3d) [options]
+  giraffe terrain export (obj|ply|stl|csv|npy) [options]
+  giraffe config show [options]
+  giraffe sweep <parameter>... [options]
 
 Options:
-  -h --help                Show this screen.
-  --length=<length>        Number of generations in simulation
-  --size=<size>            Number of animals per generation
-  --color=<color>          Color value, integer >= 0
-  --speed=<speed>          Speed of lions, integer >= 0
-  --height=<height>        Height of trees, integer >= 0
-  --mutrate=<mutrate>      Rate of mutation, 0-100
-  --mutdecay=<mutdecay>    One of: none | linear | quad
-  --blendmode=<blendmode>  One of: mean | onepoint | uniform
+  -h --help                      Show this screen.
+  --length=<length>              Number of generations in simulation
+  --size=<size>                  Number of animals per generation
+  --color=<color>                Color value, integer >= 0
+  --speed=<speed>                Speed of lions, integer >= 0
+  --height=<height>              Height of trees, integer >= 0
+  --mutrate=<mutrate>            Rate of mutation, 0-100
+  --mutdecay=<mutdecay>          One of: none | linear | quad
+  --blendmode=<blendmode>        One of: mean | onepoint | uniform
+  --forage=<forage>              One of: height | canopy
+  --competition=<competition>    One of: none | crowding | partitioning
+  --compstrength=<compstrength>  Strength of competition, 0-100
+  --selection=<selection>        One of: natural | neutral, where every giraffe is equally fit
+  --markers=<markers>            Neutral marker genes per giraffe, 0-64 (default: 0)
+  --mating=<mating>              One of: random | assortative | disassortative | preference
+  --matetrait=<matetrait>        Trait compared by assortative mating, one of: color | height
+  --candidates=<candidates>      Mates drawn for each choice, 1-100 (default: 5)
+  --inbreeding=<inbreeding>      One of: allow | avoid, where giraffes turn away siblings and themselves
+  --xaxis=<xaxis>                Terrain x axis, one of: legs | neck | color | height | speed
+  --yaxis=<yaxis>                Terrain y axis, one of: legs | neck | color | height | speed
+  --xrange=<xrange>              Terrain x axis range, as min:max
+  --yrange=<yrange>              Terrain y axis range, as min:max
+  --resolution=<resolution>      Terrain grid points along each axis
+  --fix=<fix>                    Hold other terrain traits at values, e.g. color=500 (default: optimum)
+  --diversity                    Plot genetic diversity and selection metrics
+  --histograms                   Plot per-generation color, height and speed histograms
+  --clusters                     Cluster the tower by legs, neck and color every generation and track the clusters
+  --boxplots=<generations>       Plot trait quartiles every N generations with bimodality coefficients (default: 50)
+  --loci                         Plot and export per-locus gene values, flagging loci that moved after environment changes
+  --frames=<frames>              Render landscape and density frames every N generations
+  --outdir=<outdir>              Directory to write generated files to (default: .)
+  --name=<name>                  Run name, prefixed to generated file names
+  --format=<format>              Figure format, one of: png | svg | pdf
+  --imagesize=<imagesize>        Figure size in pixels, as widthxheight (default: 640x480)
+  --figures=<figures>            Figures to plot, e.g. color,height,speed,fitness,diversity
+  --log-format=<format>          Progress output, one of: tui | text | json (default: tui, text when piped)
+  --quiet                        Don't report progress during the simulation
+  --replicates=<replicates>      Seeded runs of the simulation (default: 1) or of each sweep combination (default: 5)
+  --seed=<seed>                  Seed of the first run, each further replicate adds one (default: random)
+  --threads=<threads>            Worker threads for replicates and sweeps (default: available cores)
+  --tolerance=<tolerance>        Convergence tolerance around each trait target, percent (default: 5)
+  --stop-on-convergence          Stop once every trait mean has held within the tolerance for 50 generations
+  --target-fitness=<fitness>     Stop once mean fitness reaches this, at most 3.5
+  --stall=<generations>          Stop after this many generations without a higher mean fitness
+  --min-diversity=<bits>         Stop once mean allele entropy falls below this, 0-8 bits
+  --time-limit=<seconds>         Stop after running this many seconds
+  --genealogy                    Track every giraffe's parents and export lineages, coalescence times and a sampled genealogy
+  --sample=<sample>              Giraffes of the final generation in the exported genealogy (default: 16)
+  --depth=<depth>                Generations of both parents in the exported DOT genealogy (default: 8)
 ";
 
 #[derive(Debug, RustcDecodable)]
 struct Args {
-    cmd_terrain:    bool,
-    cmd_plot:       bool,
-    cmd_3d:         bool,
-    flag_length:    Option<u32>,
-    flag_size:      Option<u16>,
-    flag_color:     Option<u32>,
-    flag_speed:     Option<u32>,
-    flag_height:    Option<u32>,
-    flag_mutrate:   Option<u8>,
-    flag_mutdecay:  Option<String>,
-    flag_blendmode: Option<String>
+    cmd_terrain:              bool,
+    cmd_plot:                 bool,
+    cmd_3d:                   bool,
+    cmd_overlay:              bool,
+    cmd_contour:              bool,
+    cmd_heatmap:              bool,
+    cmd_analyze:              bool,
+    cmd_export:               bool,
+    cmd_obj:                  bool,
+    cmd_ply:                  bool,
+    cmd_stl:                  bool,
+    cmd_csv:                  bool,
+    cmd_npy:                  bool,
+    cmd_config:               bool,
+    cmd_show:                 bool,
+    cmd_sweep:                bool,
+    arg_parameter:            Vec<String>,
+    flag_length:              Option<String>,
+    flag_size:                Option<String>,
+    flag_color:               Option<String>,
+    flag_speed:               Option<String>,
+    flag_height:              Option<String>,
+    flag_mutrate:             Option<String>,
+    flag_mutdecay:            Option<String>,
+    flag_blendmode:           Option<String>,
+    flag_forage:              Option<String>,
+    flag_competition:         Option<String>,
+    flag_compstrength:        Option<String>,
+    flag_selection:           Option<String>,
+    flag_markers:             Option<String>,
+    flag_mating:              Option<String>,
+    flag_matetrait:           Option<String>,
+    flag_candidates:          Option<String>,
+    flag_inbreeding:          Option<String>,
+    flag_diversity:           bool,
+    flag_histograms:          bool,
+    flag_boxplots:            Option<u32>,
+    flag_loci:                bool,
+    flag_clusters:            bool,
+    flag_frames:              Option<u32>,
+    flag_xaxis:               Option<String>,
+    flag_yaxis:               Option<String>,
+    flag_xrange:              Option<String>,
+    flag_yrange:              Option<String>,
+    flag_resolution:          Option<usize>,
+    flag_fix:                 Option<String>,
+    flag_outdir:              Option<String>,
+    flag_name:                Option<String>,
+    flag_format:              Option<String>,
+    flag_imagesize:           Option<String>,
+    flag_figures:             Option<String>,
+    flag_log_format:          Option<String>,
+    flag_quiet:               bool,
+    flag_replicates:          Option<u32>,
+    flag_seed:                Option<u64>,
+    flag_threads:             Option<usize>,
+    flag_tolerance:           Option<String>,
+    flag_stop_on_convergence: bool,
+    flag_target_fitness:      Option<f64>,
+    flag_stall:               Option<u32>,
+    flag_min_diversity:       Option<f64>,
+    flag_time_limit:          Option<f64>,
+    flag_genealogy:           bool,
+    flag_sample:              Option<usize>,
+    flag_depth:               Option<u32>
+}
+
+// How a simulation is run and reported, as opposed to what is simulated.
+struct RunSettings {
+    log_format:  reporter::LogFormat,
+    frames:      Option<u32>,
+    boxplots:    Option<u32>,
+    replicates:  u32,
+    threads:     usize,
+    criteria:    convergence::Criteria,
+    rules:       termination::Rules,
+    sample_size: usize
+}
+
+fn run_simulation(
+    args:         &Args,
+    options:      options::Options,
+    terrain_spec: &terrain::TerrainSpec,
+    settings:     RunSettings,
+    mut output:   output::Output
+) {
+    let seed          = args.flag_seed.unwrap_or_else(giraffe_lib::random_u64);
+    let mut notes     = settings.log_format.notes();
+    let mut reporter  = reporter::Reporter::new(settings.log_format, options.simulation_length);
+    let mut animation = settings.frames.map(|interval| {
+        let terminal  = output.terminal();
+        let extension = output.format.extension();
+
+        animation::Animation::new(&output.file("frames"), interval, &terrain_spec, &terminal, extension)
+            .expect("Failed to create frames directory")
+    });
+    let mut pedigree = if args.flag_genealogy || output.is_selected("lineages") {
+        Some(genealogy::Pedigree::new())
+    } else {
+        None
+    };
+    let mut clusters = if args.flag_clusters || output.is_selected("clusters") {
+        Some(clustering::ClusterHistory::new())
+    } else {
+        None
+    };
+
+    writeln!(notes, "seed: {}", seed).expect("Failed to print");
+
+    giraffe_lib::seed_random(seed);
+
+    let statistics = simulation::simulate_until(&options, settings.rules, |world, statistics| {
+        reporter.report(&statistics);
+
+        if let Some(ref mut animation) = animation {
+            animation.record(&world);
+        }
+
+        if let Some(ref mut pedigree) = pedigree {
+            pedigree.record(&world);
+        }
+
+        if let Some(ref mut clusters) = clusters {
+            clusters.record(&world);
+        }
+    });
+
+    reporter.finish(&statistics);
+
+    let reason      = statistics.termination().unwrap_or(termination::Reason::Completed);
+    let generations = statistics.latest().map_or(0, |latest| latest.generation);
+
+    if reason != termination::Reason::Completed {
+        writeln!(notes, "Stopped at generation {}: {}", generations, reason.describe()).expect("Failed to print");
+    }
+
+    // Further replicates run quietly on the worker threads, seeded seed + 1
+    // onwards, and are summarized together with the run above.
+    let replicates = if settings.replicates > 1 {
+        let jobs = (1..settings.replicates).map(|replicate| {
+            simulation::Job {
+                options: options.clone(),
+                rules:   settings.rules,
+                seed:    seed.wrapping_add(replicate as u64)
+            }
+        }).collect::<Vec<simulation::Job>>();
+
+        let others = simulation::simulate_all(jobs, settings.threads, |statistics| statistics, |finished, _| {
+            writeln!(notes, "Finished replicate {}/{}", finished + 1, settings.replicates).expect("Failed to print");
+        });
+
+        let replicates  = replicates::Replicates::new(&iter::once(&statistics).chain(others.iter()).collect());
+        let destination = output.file("replicates.csv");
+
+        replicates.export_csv(&destination).expect("Failed to write replicates.csv");
+        writeln!(notes, "Aggregated {} replicates to {:?}", settings.replicates, destination).expect("Failed to print");
+
+        Some(replicates)
+    } else {
+        None
+    };
+
+    let terminal = output.terminal();
+
+    if let Some(destination) = output.figure("color", false) {
+        statistics.generate_color_figure(&terminal, &destination, replicates.as_ref());
+    }
+
+    if let Some(destination) = output.figure("height", false) {
+        statistics.generate_height_figure(&terminal, &destination, replicates.as_ref());
+    }
+
+    if let Some(destination) = output.figure("speed", false) {
+        statistics.generate_speed_figure(&terminal, &destination, replicates.as_ref());
+    }
+
+    if let Some(destination) = output.figure("fitness", false) {
+        statistics.generate_fitness_figure(&terminal, &destination);
+    }
+
+    if let Some(destination) = output.figure("dashboard", false) {
+        statistics.generate_dashboard_figure(&terminal, &destination);
+    }
+
+    if let Some(destination) = output.figure("diversity", args.flag_diversity) {
+        statistics.generate_diversity_figure(&terminal, &destination);
+    }
+
+    if let Some(destination) = output.figure("color_histogram", args.flag_histograms) {
+        statistics.generate_color_histogram_figure(&terminal, &destination);
+    }
+
+    if let Some(destination) = output.figure("height_histogram", args.flag_histograms) {
+        statistics.generate_height_histogram_figure(&terminal, &destination);
+    }
+
+    if let Some(destination) = output.figure("speed_histogram", args.flag_histograms) {
+        statistics.generate_speed_histogram_figure(&terminal, &destination);
+    }
+
+    if let Some(destination) = output.figure("distributions", settings.boxplots.is_some()) {
+        let interval = settings.boxplots.unwrap_or(defaults::BOXPLOT_INTERVAL);
+
+        statistics.generate_distribution_figure(&terminal, &destination, interval);
+    }
+
+    if let Some(destination) = output.figure("loci", args.flag_loci) {
+        statistics.loci().generate_figure(&terminal, &destination, &statistics.environment_changes());
+    }
+
+    if let Some(destination) = output.figure("allele_frequencies", args.flag_loci) {
+        let shifts = statistics.loci().shifts(
+            &statistics.environment_changes(),
+            defaults::SHIFT_WINDOW,
+            defaults::SHIFT_THRESHOLD
+        );
+
+        statistics.loci().generate_frequency_figure(&terminal, &destination, &shifts, &statistics.environment_changes());
+    }
+
+    if args.flag_loci {
+        export_loci(&statistics, &mut output, &mut notes);
+    }
+
+    // Drift is compared with Wright–Fisher whenever there is something
+    // neutral to follow and genes are inherited whole.
+    let drift = if options.markers > 0 || options.selection == selection::Selection::Neutral {
+        if drift::applies_to(options.blending_mode) {
+            Some(drift::Drift::new(&statistics, &options))
+        } else {
+            writeln!(
+                notes,
+                "Skipped the Wright-Fisher drift comparison, mean blending doesn't copy genes from one parent"
+            ).expect("Failed to print");
+            None
+        }
+    } else {
+        None
+    };
+
+    if let Some(ref drift) = drift {
+        if let Some(destination) = output.figure("drift", true) {
+            drift.generate_figure(&terminal, &destination);
+        }
+
+        drift.export_csv(&output.file("drift.csv")).expect("Failed to write drift.csv");
+    }
+
+    if let Some(ref clusters) = clusters {
+        if let Some(destination) = output.figure("clusters", args.flag_clusters) {
+            clusters.generate_figure(&terminal, &destination);
+        }
+
+        clusters.export_csv(&output.file("clusters.csv")).expect("Failed to write clusters.csv");
+    }
+
+    if let Some(ref pedigree) = pedigree {
+        if let Some(destination) = output.figure("lineages", args.flag_genealogy) {
+            pedigree.generate_lineages_figure(&terminal, &destination);
+        }
+
+        if args.flag_genealogy {
+            export_genealogy(&args, &pedigree, settings.sample_size, &mut output, &mut notes);
+        }
+    }
+
+    if let Some(ref animation) = animation {
+        animation.print_instructions(&mut notes).expect("Failed to print");
+    }
+
+    statistics.export_csv(&output.file("statistics.csv")).expect("Failed to write statistics.csv");
+
+    let summary = simulation::Summary {
+        seed:        seed,
+        generations: generations,
+        termination: reason.name().to_string(),
+        convergence: convergence::Convergence::new(&statistics, settings.criteria)
+    };
+
+    let mut file = File::create(&output.file("summary.json")).expect("Failed to create summary.json");
+
+    write!(file, "{}", json::as_pretty_json(&summary)).expect("Failed to write summary.json");
+
+    let manifest = output.write_manifest(&options).expect("Failed to write manifest");
+    writeln!(notes, "Listed generated files in {:?}", manifest).expect("Failed to print");
+
+    writeln!(notes, "termination: {} at generation {}", reason.name(), generations).expect("Failed to print");
+    statistics.print_summary(&mut notes).expect("Failed to print");
+    summary.convergence.print_summary(&mut notes).expect("Failed to print");
+
+    if let Some(ref drift) = drift {
+        drift.print_summary(&mut notes).expect("Failed to print");
+    }
+
+    if let Some(ref clusters) = clusters {
+        clusters.print_summary(&mut notes).expect("Failed to print");
+    }
+
+    reporter.report_summary(&summary);
 }
 
-fn run_simulation(options: options::Options) {
-    let mut statistics = statistics::Statistics::new();
-    let mut world      = world::World::new(options);
+fn export_loci<W: Write>(statistics: &statistics::Statistics, output: &mut output::Output, notes: &mut W) {
+    let shifts = statistics.loci().shifts(
+        &statistics.environment_changes(),
+        defaults::SHIFT_WINDOW,
+        defaults::SHIFT_THRESHOLD
+    );
 
-    statistics.update(&world);
-    statistics.print_latest();
+    statistics.loci().export_csv(&output.file("loci.csv")).expect("Failed to write loci.csv");
+    loci::export_shifts_csv(&output.file("locus_shifts.csv"), &shifts).expect("Failed to write locus_shifts.csv");
 
-    for _ in 0..world.options.simulation_length {
-        world = world.evolve();
-        statistics.update(&world);
-        statistics.print_latest();
+    for shift in shifts.iter().filter(|shift| shift.significant) {
+        writeln!(
+            notes,
+            "locus {} moved from {:.1} to {:.1} after the environment changed at generation {}, {:.1} times the expected drift",
+            shift.locus,
+            shift.before,
+            shift.after,
+            shift.generation,
+            shift.score
+        ).expect("Failed to print");
     }
+}
+
+fn export_genealogy<W: Write>(
+    args:        &Args,
+    pedigree:    &genealogy::Pedigree,
+    sample_size: usize,
+    output:      &mut output::Output,
+    notes:       &mut W
+) {
+    let sample      = pedigree.sample(sample_size);
+    let depth       = args.flag_depth.unwrap_or(defaults::GENEALOGY_DEPTH);
+    let coalescence = pedigree.coalescence(&sample);
+
+    pedigree.export_csv(&output.file("genealogy.csv")).expect("Failed to write genealogy.csv");
+    pedigree.export_dot(&output.file("genealogy.dot"), &sample, depth).expect("Failed to write genealogy.dot");
+    pedigree.export_newick(&output.file("genealogy.nwk"), &sample).expect("Failed to write genealogy.nwk");
 
-    statistics.generate_color_figure("color_output.png");
-    statistics.generate_height_figure("height_output.png");
-    statistics.generate_speed_figure("speed_output.png");
+    let mut file = File::create(&output.file("genealogy.json")).expect("Failed to create genealogy.json");
+
+    write!(file, "{}", json::as_pretty_json(&coalescence)).expect("Failed to write genealogy.json");
+
+    writeln!(
+        notes,
+        "genealogy: {} founder lineages and {} founders with descendants remain, sample TMRCA: {}, mean pairwise coalescence: {}",
+        coalescence.founder_lineages,
+        coalescence.founders_represented,
+        coalescence.sample_tmrca.map_or("none".to_string(), |tmrca| format!("{} generations", tmrca)),
+        coalescence.mean_pairwise_coalescence.map_or("none".to_string(), |mean| format!("{:.1} generations", mean))
+    ).expect("Failed to print");
 }
 
-fn handle_terrain_command(args: &Args, options: options::Options) {
-    if args.cmd_plot {
-        let destination = "fitness_terrain.png";
-        solution_space::render_plot(destination, options);
+fn handle_sweep_command(args: &Args, config: &config::Config, options: options::Options, mut output: output::Output) {
+    let combinations = make_combinations_from_args(&args, &config).unwrap_or_else(|e| exit_with_error(e));
+    let replicates   = make_replicates_from_args(&args, defaults::SWEEP_REPLICATES).unwrap_or_else(|e| exit_with_error(e));
+    let threads      = make_threads_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+    let seed         = args.flag_seed.unwrap_or_else(giraffe_lib::random_u64);
+    let criteria     = make_criteria_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+    let rules        = make_rules_from_args(&args, criteria).unwrap_or_else(|e| exit_with_error(e));
+    let mut notes    = make_log_format_from_args(&args).unwrap_or_else(|e| exit_with_error(e)).notes();
+
+    writeln!(
+        notes,
+        "Sweeping {} combinations with {} replicates each on {} threads, seed {}",
+        combinations.len(), replicates, threads, seed
+    ).expect("Failed to print");
+
+    let outcomes = sweep::run(&combinations, replicates, seed, threads, rules, &mut notes);
+
+    sweep::print_table(&mut notes, &combinations, &outcomes).expect("Failed to print");
+
+    let destination = output.file("sweep.csv");
+    sweep::write_summary_csv(&destination, &combinations, &outcomes, replicates).expect("Failed to write sweep.csv");
+    writeln!(notes, "Wrote sweep summary to {:?}", destination).expect("Failed to print");
+
+    let destination = output.file("sweep_runs.csv");
+    sweep::write_runs_csv(&destination, &combinations, &outcomes).expect("Failed to write sweep_runs.csv");
+    writeln!(notes, "Wrote individual sweep runs to {:?}", destination).expect("Failed to print");
+
+    output.write_manifest(&options).expect("Failed to write manifest");
+}
+
+fn handle_terrain_command(
+    args:       &Args,
+    options:    options::Options,
+    spec:       &terrain::TerrainSpec,
+    frames:     Option<u32>,
+    mut output: output::Output
+) {
+    if args.cmd_overlay {
+        handle_overlay_command(&args, &spec, options.clone(), frames, &mut output);
+    } else if args.cmd_plot {
+        let destination = output.image("fitness_terrain");
+        solution_space::render_plot(&output.terminal(), &destination, &spec, options.clone());
         println!("Generated fitness terrain plot to {:?}", destination);
+    } else if args.cmd_contour {
+        let destination = output.image("fitness_contour");
+        solution_space::render_contour(&output.terminal(), &destination, &spec, options.clone());
+        println!("Generated fitness contour plot to {:?}", destination);
+    } else if args.cmd_analyze {
+        let destination = output.file("terrain_analysis.json");
+        let analysis    = analysis::analyze(&spec, &options);
+        let mut file    = File::create(&destination).expect("Failed to create terrain analysis file");
+
+        write!(file, "{}", json::as_pretty_json(&analysis)).expect("Failed to write terrain analysis");
+        println!("Generated fitness terrain analysis to {:?}", destination);
+    } else if args.cmd_export {
+        let format = if args.cmd_obj {
+            export::Format::Obj
+        } else if args.cmd_ply {
+            export::Format::Ply
+        } else if args.cmd_stl {
+            export::Format::Stl
+        } else if args.cmd_csv {
+            export::Format::Csv
+        } else {
+            export::Format::Npy
+        };
+
+        let destination = output.file(&format!("fitness_terrain.{}", format.extension()));
+
+        export::export(&destination, format, &spec, &options).expect("Failed to export fitness terrain");
+        println!("Exported fitness terrain to {:?}", destination);
+    } else if args.cmd_heatmap {
+        let destination = output.image("fitness_heatmap");
+        solution_space::render_heatmap(&output.terminal(), &destination, &spec, options.clone());
+        println!("Generated fitness heatmap to {:?}", destination);
     } else {
         println!("Preparing to render 3d environment...");
-        solution_space::render_3d(options);
+        viewer::render_3d(&spec, options.clone());
+    }
+
+    // The kiss3d windows don't leave anything behind to list.
+    if !args.cmd_3d {
+        output.write_manifest(&options).expect("Failed to write manifest");
     }
 }
 
-fn make_options_from_args(args: &Args) -> options::Options {
-    let mut options = options::Options::default();
+fn handle_overlay_command(
+    args:    &Args,
+    spec:    &terrain::TerrainSpec,
+    options: options::Options,
+    frames:  Option<u32>,
+    output:  &mut output::Output
+) {
+    if args.cmd_plot {
+        let directory = output.file("overlay");
+        let interval  = frames.unwrap_or(10);
+        let terminal  = output.terminal();
+        let extension = output.format.extension();
 
-    if let Some(length) = args.flag_length {
-        options.simulation_length = length;
+        solution_space::render_overlay_plots(&directory, interval, &spec, options, &terminal, extension)
+            .expect("Failed to create overlay directory");
+        println!("Generated fitness terrain overlay plots to {:?}", directory);
+    } else {
+        println!("Preparing to render 3d overlay environment...");
+        solution_space::render_overlay_3d(&spec, options);
     }
+}
 
-    if let Some(size) = args.flag_size {
-        options.herd_size = size;
+fn parse_name<T, F>(option: &str, value: &str, expected: &'static str, from_name: F) -> Result<T, OptionsError>
+    where F: Fn(&str) -> Option<T>
+{
+    from_name(value).ok_or_else(|| OptionsError::UnknownValue {
+        option:   option.to_string(),
+        value:    value.to_string(),
+        expected: expected
+    })
+}
+
+fn parse_number<T: FromStr>(option: &str, value: &str) -> Result<T, OptionsError> {
+    value.trim().parse::<T>().map_err(|_| OptionsError::MalformedValue {
+        option:   option.to_string(),
+        value:    value.to_string(),
+        expected: "a whole number"
+    })
+}
+
+fn parse_percentage(option: &str, value: &str) -> Result<f32, OptionsError> {
+    let percentage = value.trim().parse::<f32>().map_err(|_| OptionsError::MalformedValue {
+        option:   option.to_string(),
+        value:    value.to_string(),
+        expected: "a percentage"
+    })?;
+
+    if !(percentage >= 0.0 && percentage <= 100.0) {
+        return Err(OptionsError::OutOfRange {
+            option: option.to_string(),
+            value:  percentage as f64,
+            min:    0.0,
+            max:    100.0
+        });
     }
 
-    if let Some(color) = args.flag_color {
-        options.color = color;
+    Ok(percentage / 100.0)
+}
+
+// Parses a layered setting, naming where it came from if it's invalid.
+fn parse_setting<T, F>(config: &config::Config, key: &str, parse: F) -> Result<Option<T>, OptionsError>
+    where F: Fn(&str, &str) -> Result<T, OptionsError>
+{
+    match config.get(key) {
+        Some(setting) => parse(&setting.option(key), &setting.value).map(Some),
+        None          => Ok(None)
     }
+}
 
-    if let Some(lion_speed) = args.flag_speed {
-        options.lion_speed = lion_speed;
+fn parse_axis_range(option: &str, range: &str, axis: &mut terrain::Axis) -> Result<(), OptionsError> {
+    let (min, max) = terrain::parse_range(range).ok_or_else(|| OptionsError::MalformedValue {
+        option:   option.to_string(),
+        value:    range.to_string(),
+        expected: "min:max with min < max"
+    })?;
+
+    let (_, limit) = axis.phenotype.full_range();
+
+    if max > limit {
+        return Err(OptionsError::OutOfRange {
+            option: option.to_string(),
+            value:  max as f64,
+            min:    0.0,
+            max:    limit as f64
+        });
     }
 
-    if let Some(tree_height) = args.flag_height {
-        options.tree_height = tree_height;
+    axis.min = min;
+    axis.max = max;
+
+    Ok(())
+}
+
+fn make_terrain_spec_from_args(args: &Args) -> Result<terrain::TerrainSpec, OptionsError> {
+    let mut spec = terrain::TerrainSpec::default();
+
+    let phenotypes = "legs | neck | color | height | speed";
+
+    if let Some(ref name) = args.flag_xaxis {
+        spec.x_axis = terrain::Axis::new(parse_name("--xaxis", name, phenotypes, terrain::Phenotype::from_name)?);
     }
 
-    if let Some(mutation_rate) = args.flag_mutrate {
-        options.mutation_rate = mutation_rate as f32 / 100.0
+    if let Some(ref name) = args.flag_yaxis {
+        spec.y_axis = terrain::Axis::new(parse_name("--yaxis", name, phenotypes, terrain::Phenotype::from_name)?);
     }
 
-    if let Some(ref mutation_decay) = args.flag_mutdecay {
-        options.mutation_decay = match mutation_decay.as_ref() {
-            "linear" => mutation::MutationDecay::Linear,
-            "quad"   => mutation::MutationDecay::Quadratic,
-            _        => mutation::MutationDecay::None
+    if spec.x_axis.phenotype == spec.y_axis.phenotype {
+        return Err(OptionsError::SameAxes { phenotype: spec.x_axis.phenotype.name() });
+    }
+
+    if let Some(ref range) = args.flag_xrange {
+        parse_axis_range("--xrange", range, &mut spec.x_axis)?;
+    }
+
+    if let Some(ref range) = args.flag_yrange {
+        parse_axis_range("--yrange", range, &mut spec.y_axis)?;
+    }
+
+    if let Some(resolution) = args.flag_resolution {
+        if resolution < 2 || resolution > terrain::MAX_RESOLUTION {
+            return Err(OptionsError::OutOfRange {
+                option: "--resolution".to_string(),
+                value:  resolution as f64,
+                min:    2.0,
+                max:    terrain::MAX_RESOLUTION as f64
+            });
         }
+
+        spec.resolution = resolution;
+    }
+
+    if let Some(ref fixed) = args.flag_fix {
+        spec.fixed = terrain::parse_fixed_values(fixed).ok_or_else(|| OptionsError::MalformedValue {
+            option:   "--fix".to_string(),
+            value:    fixed.to_string(),
+            expected: "comma separated trait=value pairs, e.g. color=500,neck=900"
+        })?;
+    }
+
+    Ok(spec)
+}
+
+fn make_log_format_from_args(args: &Args) -> Result<reporter::LogFormat, OptionsError> {
+    if args.flag_quiet {
+        return Ok(reporter::LogFormat::Quiet);
+    }
+
+    match args.flag_log_format {
+        Some(ref format) => parse_name("--log-format", format, "tui | text | json", reporter::LogFormat::from_name),
+        None             => Ok(reporter::LogFormat::default_format())
     }
+}
 
-    if let Some(ref blending_mode) = args.flag_blendmode {
-        options.blending_mode = match blending_mode.as_ref() {
-            "mean"     => blending::BlendingMode::Mean,
-            "onepoint" => blending::BlendingMode::OnePointCrossover,
-            _          => blending::BlendingMode::UniformCrossover
+fn make_output_from_args(args: &Args) -> Result<output::Output, OptionsError> {
+    let mut output = output::Output::default();
+
+    if let Some(ref directory) = args.flag_outdir {
+        output.directory = directory.clone();
+    }
+
+    output.name = args.flag_name.clone();
+
+    if let Some(ref format) = args.flag_format {
+        output.format = parse_name("--format", format, "png | svg | pdf", output::ImageFormat::from_name)?;
+    }
+
+    if let Some(ref size) = args.flag_imagesize {
+        let (width, height) = output::parse_image_size(size).ok_or_else(|| OptionsError::MalformedValue {
+            option:   "--imagesize".to_string(),
+            value:    size.to_string(),
+            expected: "widthxheight, e.g. 1200x800"
+        })?;
+
+        output.width  = width;
+        output.height = height;
+    }
+
+    if let Some(ref figures) = args.flag_figures {
+        let mut selected = vec![];
+
+        for figure in figures.split(',').map(|figure| figure.trim()).filter(|figure| !figure.is_empty()) {
+            let name = parse_name("--figures", figure, output::FIGURE_NAMES, |name| {
+                if output::is_figure(name) { Some(name.to_string()) } else { None }
+            })?;
+
+            selected.push(name);
         }
+
+        output.figures = Some(selected);
     }
 
-    println!("options: {:?}", options);
+    Ok(output)
+}
+
+// The command line is the last layer of the config.
+fn apply_flags(config: &mut config::Config, args: &Args) {
+    let flags = vec![
+        ("length", &args.flag_length),
+        ("size", &args.flag_size),
+        ("color", &args.flag_color),
+        ("speed", &args.flag_speed),
+        ("height", &args.flag_height),
+        ("mutrate", &args.flag_mutrate),
+        ("mutdecay", &args.flag_mutdecay),
+        ("blendmode", &args.flag_blendmode),
+        ("forage", &args.flag_forage),
+        ("competition", &args.flag_competition),
+        ("compstrength", &args.flag_compstrength),
+        ("selection", &args.flag_selection),
+        ("markers", &args.flag_markers),
+        ("mating", &args.flag_mating),
+        ("matetrait", &args.flag_matetrait),
+        ("candidates", &args.flag_candidates),
+        ("inbreeding", &args.flag_inbreeding)
+    ];
 
-    options
+    for (key, flag) in flags {
+        if let Some(ref value) = *flag {
+            config.set(key, value.clone(), config::Source::Flag);
+        }
+    }
+}
+
+fn make_options_from_config(config: &config::Config) -> Result<options::Options, OptionsError> {
+    let mut builder = options::OptionsBuilder::new();
+
+    if let Some(length) = parse_setting(&config, "length", parse_number)? {
+        builder = builder.simulation_length(length);
+    }
+
+    if let Some(size) = parse_setting(&config, "size", parse_number)? {
+        builder = builder.herd_size(size);
+    }
+
+    if let Some(color) = parse_setting(&config, "color", parse_number)? {
+        builder = builder.color(color);
+    }
+
+    if let Some(lion_speed) = parse_setting(&config, "speed", parse_number)? {
+        builder = builder.lion_speed(lion_speed);
+    }
+
+    if let Some(tree_height) = parse_setting(&config, "height", parse_number)? {
+        builder = builder.tree_height(tree_height);
+    }
+
+    if let Some(mutation_rate) = parse_setting(&config, "mutrate", parse_percentage)? {
+        builder = builder.mutation_rate(mutation_rate);
+    }
+
+    if let Some(mutation_decay) = parse_setting(&config, "mutdecay", |option, value| {
+        parse_name(option, value, "none | linear | quad", mutation::MutationDecay::from_name)
+    })? {
+        builder = builder.mutation_decay(mutation_decay);
+    }
+
+    if let Some(blending_mode) = parse_setting(&config, "blendmode", |option, value| {
+        parse_name(option, value, "mean | onepoint | uniform", blending::BlendingMode::from_name)
+    })? {
+        builder = builder.blending_mode(blending_mode);
+    }
+
+    if let Some(forage_model) = parse_setting(&config, "forage", |option, value| {
+        parse_name(option, value, "height | canopy", canopy::ForageModel::from_name)
+    })? {
+        builder = builder.forage_model(forage_model);
+    }
+
+    if let Some(competition) = parse_setting(&config, "competition", |option, value| {
+        parse_name(option, value, "none | crowding | partitioning", competition::Competition::from_name)
+    })? {
+        builder = builder.competition(competition);
+    }
+
+    if let Some(competition_strength) = parse_setting(&config, "compstrength", parse_percentage)? {
+        builder = builder.competition_strength(competition_strength);
+    }
+
+    if let Some(selection) = parse_setting(&config, "selection", |option, value| {
+        parse_name(option, value, "natural | neutral", selection::Selection::from_name)
+    })? {
+        builder = builder.selection(selection);
+    }
+
+    if let Some(markers) = parse_setting(&config, "markers", parse_number)? {
+        builder = builder.markers(markers);
+    }
+
+    if let Some(mating) = parse_setting(&config, "mating", |option, value| {
+        parse_name(option, value, "random | assortative | disassortative | preference", mating::Mating::from_name)
+    })? {
+        builder = builder.mating(mating);
+    }
+
+    if let Some(mating_trait) = parse_setting(&config, "matetrait", |option, value| {
+        parse_name(option, value, "color | height", mating::MatingTrait::from_name)
+    })? {
+        builder = builder.mating_trait(mating_trait);
+    }
+
+    if let Some(mate_candidates) = parse_setting(&config, "candidates", parse_number)? {
+        builder = builder.mate_candidates(mate_candidates);
+    }
+
+    if let Some(inbreeding) = parse_setting(&config, "inbreeding", |option, value| {
+        parse_name(option, value, "allow | avoid", mating::Inbreeding::from_name)
+    })? {
+        builder = builder.inbreeding(inbreeding);
+    }
+
+    builder.build()
+}
+
+// Each combination of the swept values is layered over the config as if
+// passed as flags, and checked like any other options.
+fn make_combinations_from_args(args: &Args, config: &config::Config) -> Result<Vec<sweep::Combination>, OptionsError> {
+    let parameters = args.arg_parameter.iter().map(|parameter| {
+        sweep::parse_parameter(parameter)
+    }).collect::<Result<Vec<sweep::Parameter>, OptionsError>>()?;
+
+    sweep::combinations(&parameters).into_iter().map(|settings| {
+        let mut config = config.clone();
+
+        for &(key, ref value) in &settings {
+            config.set(key, value.clone(), config::Source::Flag);
+        }
+
+        make_options_from_config(&config).map(|options| {
+            sweep::Combination { settings: settings, options: options }
+        })
+    }).collect()
+}
+
+fn make_replicates_from_args(args: &Args, default: u32) -> Result<u32, OptionsError> {
+    match args.flag_replicates {
+        Some(0) => Err(OptionsError::OutOfRange {
+            option: "--replicates".to_string(),
+            value:  0.0,
+            min:    1.0,
+            max:    ::std::u32::MAX as f64
+        }),
+        Some(replicates) => Ok(replicates),
+        None             => Ok(default)
+    }
+}
+
+fn make_frames_from_args(args: &Args) -> Result<Option<u32>, OptionsError> {
+    match args.flag_frames {
+        Some(0) => Err(OptionsError::OutOfRange {
+            option: "--frames".to_string(),
+            value:  0.0,
+            min:    1.0,
+            max:    ::std::u32::MAX as f64
+        }),
+        frames => Ok(frames)
+    }
+}
+
+fn make_boxplots_from_args(args: &Args) -> Result<Option<u32>, OptionsError> {
+    match args.flag_boxplots {
+        Some(0) => Err(OptionsError::OutOfRange {
+            option: "--boxplots".to_string(),
+            value:  0.0,
+            min:    1.0,
+            max:    ::std::u32::MAX as f64
+        }),
+        boxplots => Ok(boxplots)
+    }
+}
+
+fn make_sample_from_args(args: &Args) -> Result<usize, OptionsError> {
+    match args.flag_sample {
+        Some(0) => Err(OptionsError::OutOfRange {
+            option: "--sample".to_string(),
+            value:  0.0,
+            min:    1.0,
+            max:    ::std::u32::MAX as f64
+        }),
+        Some(sample) => Ok(sample),
+        None         => Ok(defaults::GENEALOGY_SAMPLE)
+    }
+}
+
+fn make_threads_from_args(args: &Args) -> Result<usize, OptionsError> {
+    match args.flag_threads {
+        Some(0) => Err(OptionsError::OutOfRange {
+            option: "--threads".to_string(),
+            value:  0.0,
+            min:    1.0,
+            max:    ::std::u32::MAX as f64
+        }),
+        Some(threads) => Ok(threads),
+        None          => Ok(thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1))
+    }
+}
+
+fn make_criteria_from_args(args: &Args) -> Result<convergence::Criteria, OptionsError> {
+    let tolerance = match args.flag_tolerance {
+        Some(ref tolerance) => parse_percentage("--tolerance", tolerance)? as f64,
+        None                => defaults::CONVERGENCE_TOLERANCE
+    };
+
+    Ok(convergence::Criteria { tolerance: tolerance, window: defaults::CONVERGENCE_WINDOW })
+}
+
+fn check_range(option: &str, value: f64, min: f64, max: f64) -> Result<f64, OptionsError> {
+    if value >= min && value <= max {
+        Ok(value)
+    } else {
+        Err(OptionsError::OutOfRange { option: option.to_string(), value: value, min: min, max: max })
+    }
+}
+
+fn make_rules_from_args(args: &Args, criteria: convergence::Criteria) -> Result<termination::Rules, OptionsError> {
+    let mut rules = termination::Rules::default();
+
+    if let Some(target) = args.flag_target_fitness {
+        rules.target_fitness = Some(check_range("--target-fitness", target, 0.0, world::MAX_FITNESS as f64)?);
+    }
+
+    if args.flag_stop_on_convergence {
+        rules.convergence = Some(criteria);
+    }
+
+    if let Some(stall) = args.flag_stall {
+        rules.stall = Some(check_range("--stall", stall as f64, 1.0, ::std::u32::MAX as f64)? as u32);
+    }
+
+    if let Some(minimum) = args.flag_min_diversity {
+        rules.min_diversity = Some(check_range("--min-diversity", minimum, 0.0, 8.0)?);
+    }
+
+    if let Some(seconds) = args.flag_time_limit {
+        let seconds = check_range("--time-limit", seconds, 0.001, ::std::u32::MAX as f64)?;
+
+        rules.time_limit = Some(Duration::from_millis((seconds * 1000.0) as u64));
+    }
+
+    Ok(rules)
+}
+
+fn exit_with_error(error: OptionsError) -> ! {
+    eprintln!("giraffe: {}", error);
+    process::exit(1);
 }
 
 fn main () {
@@ -133,11 +1010,45 @@ fn main () {
         .and_then(|d| d.decode())
         .unwrap_or_else(|e| e.exit());
 
-    let options: options::Options = make_options_from_args(&args);
+    let mut config = config::Config::load().unwrap_or_else(|e| exit_with_error(e));
+
+    apply_flags(&mut config, &args);
+
+    let options = make_options_from_config(&config).unwrap_or_else(|e| exit_with_error(e));
+
+    if args.cmd_config {
+        config.show(&options);
+        return;
+    }
+
+    let log_format = make_log_format_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+
+    writeln!(log_format.notes(), "options: {:?}", options).expect("Failed to print");
+
+    let spec   = make_terrain_spec_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+    let output = make_output_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+    let frames = make_frames_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+
+    output.create_directory().expect("Failed to create output directory");
 
     if args.cmd_terrain {
-        handle_terrain_command(&args, options);
+        handle_terrain_command(&args, options, &spec, frames, output);
+    } else if args.cmd_sweep {
+        handle_sweep_command(&args, &config, options, output);
     } else {
-        run_simulation(options);
+        let criteria = make_criteria_from_args(&args).unwrap_or_else(|e| exit_with_error(e));
+
+        let settings = RunSettings {
+            log_format:  log_format,
+            frames:      frames,
+            boxplots:    make_boxplots_from_args(&args).unwrap_or_else(|e| exit_with_error(e)),
+            replicates:  make_replicates_from_args(&args, 1).unwrap_or_else(|e| exit_with_error(e)),
+            threads:     make_threads_from_args(&args).unwrap_or_else(|e| exit_with_error(e)),
+            criteria:    criteria,
+            rules:       make_rules_from_args(&args, criteria).unwrap_or_else(|e| exit_with_error(e)),
+            sample_size: make_sample_from_args(&args).unwrap_or_else(|e| exit_with_error(e))
+        };
+
+        run_simulation(&args, options, &spec, settings, output);
     }
 }
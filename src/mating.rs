@@ -0,0 +1,159 @@
+use giraffe::Giraffe;
+use options::Options;
+use traits::HasColor;
+use traits::HasHeight;
+
+
+// Draws allowed per candidate when related candidates are turned away.
+const MAX_DRAWS_PER_CANDIDATE: u32 = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq, RustcEncodable)]
+pub enum Mating {
+    Random,
+    Assortative,
+    Disassortative,
+    Preference
+}
+
+impl Mating {
+    pub fn from_name(name: &str) -> Option<Mating> {
+        match name {
+            "random"         => Some(Mating::Random),
+            "assortative"    => Some(Mating::Assortative),
+            "disassortative" => Some(Mating::Disassortative),
+            "preference"     => Some(Mating::Preference),
+            _                => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Mating::Random         => "random",
+            Mating::Assortative    => "assortative",
+            Mating::Disassortative => "disassortative",
+            Mating::Preference     => "preference"
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, RustcEncodable)]
+pub enum MatingTrait {
+    Color,
+    Height
+}
+
+impl MatingTrait {
+    pub fn from_name(name: &str) -> Option<MatingTrait> {
+        match name {
+            "color"  => Some(MatingTrait::Color),
+            "height" => Some(MatingTrait::Height),
+            _        => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MatingTrait::Color  => "color",
+            MatingTrait::Height => "height"
+        }
+    }
+
+    fn value(&self, giraffe: &Giraffe) -> i64 {
+        match *self {
+            MatingTrait::Color  => giraffe.color() as i64,
+            MatingTrait::Height => giraffe.height() as i64
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, RustcEncodable)]
+pub enum Inbreeding {
+    Allow,
+    Avoid
+}
+
+impl Inbreeding {
+    pub fn from_name(name: &str) -> Option<Inbreeding> {
+        match name {
+            "allow" => Some(Inbreeding::Allow),
+            "avoid" => Some(Inbreeding::Avoid),
+            _       => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Inbreeding::Allow => "allow",
+            Inbreeding::Avoid => "avoid"
+        }
+    }
+}
+
+// A giraffe is related to itself and to anyone sharing a parent with it.
+pub fn are_related(giraffe1: &Giraffe, giraffe2: &Giraffe) -> bool {
+    if giraffe1.id == giraffe2.id {
+        return true;
+    }
+
+    match (giraffe1.parent_ids, giraffe2.parent_ids) {
+        (Some((a, b)), Some((c, d))) => a == c || a == d || b == c || b == d,
+        _                            => false
+    }
+}
+
+// The chooser's mate among candidates drawn by fitness. Random mating takes
+// the first candidate. Otherwise the chooser picks from several: the closest
+// in the mating trait for assortative mating, the furthest for disassortative
+// mating, or the color closest to its preference. With inbreeding avoided,
+// relatives are turned away while draws remain.
+pub fn choose_mate<F>(options: &Options, tower: &Vec<Giraffe>, chooser: usize, mut draw: F) -> usize
+    where F: FnMut() -> usize
+{
+    let wanted = match options.mating {
+        Mating::Random => 1,
+        _              => options.mate_candidates
+    };
+
+    let max_draws = match options.inbreeding {
+        Inbreeding::Allow => wanted,
+        Inbreeding::Avoid => wanted * MAX_DRAWS_PER_CANDIDATE
+    };
+
+    let mut candidates = vec![];
+    let mut last_drawn = chooser;
+
+    for _ in 0..max_draws {
+        if candidates.len() as u32 >= wanted {
+            break;
+        }
+
+        last_drawn = draw();
+
+        if options.inbreeding == Inbreeding::Avoid && are_related(&tower[chooser], &tower[last_drawn]) {
+            continue;
+        }
+
+        candidates.push(last_drawn);
+    }
+
+    if candidates.is_empty() {
+        return last_drawn;
+    }
+
+    let giraffe = &tower[chooser];
+
+    let score = |candidate: usize| -> i64 {
+        let mate = &tower[candidate];
+
+        match options.mating {
+            Mating::Random         => 0,
+            Mating::Assortative    => -(options.mating_trait.value(mate) - options.mating_trait.value(giraffe)).abs(),
+            Mating::Disassortative => (options.mating_trait.value(mate) - options.mating_trait.value(giraffe)).abs(),
+            Mating::Preference     => -(mate.color() as i64 - giraffe.preference() as i64).abs()
+        }
+    };
+
+    candidates.iter().fold(candidates[0], |best, &candidate| {
+        if score(candidate) > score(best) { candidate } else { best }
+    })
+}
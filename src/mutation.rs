@@ -1,23 +1,51 @@
-use giraffe_lib::random_proportion;
-use rand;
+use giraffe_lib::{random_proportion, random_u8};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, RustcEncodable)]
 pub enum MutationDecay {
     None,
     Linear,
     Quadratic
 }
 
+impl MutationDecay {
+    pub fn from_name(name: &str) -> Option<MutationDecay> {
+        match name {
+            "none"   => Some(MutationDecay::None),
+            "linear" => Some(MutationDecay::Linear),
+            "quad"   => Some(MutationDecay::Quadratic),
+            _        => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MutationDecay::None      => "none",
+            MutationDecay::Linear    => "linear",
+            MutationDecay::Quadratic => "quad"
+        }
+    }
+}
+
 pub fn apply_mutations(chromosome: &Vec<u8>, mutation_rate: f32) -> Vec<u8> {
     chromosome.iter().map(|gene| {
         if random_proportion() * 100.0 <= mutation_rate as f32 {
-            rand::random::<u8>()
+            random_u8()
         } else {
             *gene
         }
     }).collect()
 }
 
+// The chance apply_mutations replaces a gene at the given rate, counting the
+// draws of random_proportion that pass its test.
+pub fn mutation_probability(mutation_rate: f32) -> f64 {
+    let draws = (0..256).filter(|&draw| {
+        (draw as f32) / ((!0 as u8) as f32) * 100.0 <= mutation_rate as f32
+    }).count();
+
+    draws as f64 / 256.0
+}
+
 pub fn calculate_mutation_rate(
     decay:             MutationDecay,
     base_rate:         f32,
@@ -1,30 +1,348 @@
+use std::error::Error;
+use std::fmt;
+
 use blending::BlendingMode;
+use canopy::ForageModel;
+use competition::Competition;
+use giraffe;
+use mating::{Inbreeding, Mating, MatingTrait};
 use mutation::MutationDecay;
+use selection::Selection;
 use defaults;
 
-#[derive(Debug)]
+pub const MAX_MATE_CANDIDATES: u32 = 100;
+
+// A standard deviation needs at least two giraffes.
+pub const MIN_HERD_SIZE: u16 = 2;
+
+#[derive(Clone, Debug, RustcEncodable)]
 pub struct Options {
-    pub blending_mode:     BlendingMode,
-    pub color:             u32,
-    pub lion_speed:        u32,
-    pub mutation_decay:    MutationDecay,
-    pub mutation_rate:     f32,
-    pub simulation_length: u32,
-    pub herd_size:         u16,
-    pub tree_height:       u32
+    pub blending_mode:        BlendingMode,
+    pub color:                u32,
+    pub competition:          Competition,
+    pub competition_strength: f32,
+    pub forage_model:         ForageModel,
+    pub inbreeding:           Inbreeding,
+    pub lion_speed:           u32,
+    pub markers:              usize,
+    pub mate_candidates:      u32,
+    pub mating:               Mating,
+    pub mating_trait:         MatingTrait,
+    pub mutation_decay:       MutationDecay,
+    pub mutation_rate:        f32,
+    pub selection:            Selection,
+    pub simulation_length:    u32,
+    pub herd_size:            u16,
+    pub tree_height:          u32
 }
 
 impl Default for Options {
     fn default() -> Self {
         Options {
-            blending_mode:     defaults::BLENDING_MODE,
-            color:             defaults::COLOR,
-            lion_speed:        defaults::LION_SPEED,
-            mutation_decay:    defaults::MUTATION_DECAY,
-            mutation_rate:     defaults::MUTATION_RATE,
-            simulation_length: defaults::SIMULATION_LENGTH,
-            herd_size:         defaults::WORLD_SIZE,
-            tree_height:       defaults::TREE_HEIGHT
+            blending_mode:        defaults::BLENDING_MODE,
+            color:                defaults::COLOR,
+            competition:          defaults::COMPETITION,
+            competition_strength: defaults::COMPETITION_STRENGTH,
+            forage_model:         defaults::FORAGE_MODEL,
+            inbreeding:           defaults::INBREEDING,
+            lion_speed:           defaults::LION_SPEED,
+            markers:              defaults::MARKERS,
+            mate_candidates:      defaults::MATE_CANDIDATES,
+            mating:               defaults::MATING,
+            mating_trait:         defaults::MATING_TRAIT,
+            mutation_decay:       defaults::MUTATION_DECAY,
+            mutation_rate:        defaults::MUTATION_RATE,
+            selection:            defaults::SELECTION,
+            simulation_length:    defaults::SIMULATION_LENGTH,
+            herd_size:            defaults::WORLD_SIZE,
+            tree_height:          defaults::TREE_HEIGHT
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionsError {
+    UnknownValue   { option: String, value: String, expected: &'static str },
+    MalformedValue { option: String, value: String, expected: &'static str },
+    OutOfRange     { option: String, value: f64, min: f64, max: f64 },
+    ZeroTarget     { option: &'static str },
+    SameAxes       { phenotype: &'static str },
+    InvalidConfig  { path: String, message: String }
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OptionsError::UnknownValue { ref option, ref value, expected } =>
+                write!(f, "unknown {} {:?}, expected one of: {}", option, value, expected),
+            OptionsError::MalformedValue { ref option, ref value, expected } =>
+                write!(f, "malformed {} {:?}, expected {}", option, value, expected),
+            OptionsError::OutOfRange { ref option, value, min, max } =>
+                write!(f, "{} must be between {} and {}, got {}", option, min, max, value),
+            OptionsError::ZeroTarget { option } =>
+                write!(f, "{} must be greater than 0, fitness is measured relative to it", option),
+            OptionsError::SameAxes { phenotype } =>
+                write!(f, "terrain x and y axes must differ, both are {}", phenotype),
+            OptionsError::InvalidConfig { ref path, ref message } =>
+                write!(f, "invalid config file {}: {}", path, message)
+        }
+    }
+}
+
+impl Error for OptionsError {}
+
+// Builds Options on top of the defaults, checking the result as a whole so an
+// invalid combination is reported instead of surfacing later as a panic or NaN
+// fitness.
+pub struct OptionsBuilder {
+    options: Options
+}
+
+impl OptionsBuilder {
+    pub fn new() -> Self {
+        OptionsBuilder { options: Options::default() }
+    }
+
+    pub fn blending_mode(mut self, blending_mode: BlendingMode) -> Self {
+        self.options.blending_mode = blending_mode;
+        self
+    }
+
+    pub fn color(mut self, color: u32) -> Self {
+        self.options.color = color;
+        self
+    }
+
+    pub fn competition(mut self, competition: Competition) -> Self {
+        self.options.competition = competition;
+        self
+    }
+
+    pub fn competition_strength(mut self, competition_strength: f32) -> Self {
+        self.options.competition_strength = competition_strength;
+        self
+    }
+
+    pub fn forage_model(mut self, forage_model: ForageModel) -> Self {
+        self.options.forage_model = forage_model;
+        self
+    }
+
+    pub fn inbreeding(mut self, inbreeding: Inbreeding) -> Self {
+        self.options.inbreeding = inbreeding;
+        self
+    }
+
+    pub fn lion_speed(mut self, lion_speed: u32) -> Self {
+        self.options.lion_speed = lion_speed;
+        self
+    }
+
+    pub fn markers(mut self, markers: usize) -> Self {
+        self.options.markers = markers;
+        self
+    }
+
+    pub fn mate_candidates(mut self, mate_candidates: u32) -> Self {
+        self.options.mate_candidates = mate_candidates;
+        self
+    }
+
+    pub fn mating(mut self, mating: Mating) -> Self {
+        self.options.mating = mating;
+        self
+    }
+
+    pub fn mating_trait(mut self, mating_trait: MatingTrait) -> Self {
+        self.options.mating_trait = mating_trait;
+        self
+    }
+
+    pub fn mutation_decay(mut self, mutation_decay: MutationDecay) -> Self {
+        self.options.mutation_decay = mutation_decay;
+        self
+    }
+
+    pub fn mutation_rate(mut self, mutation_rate: f32) -> Self {
+        self.options.mutation_rate = mutation_rate;
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.options.selection = selection;
+        self
+    }
+
+    pub fn simulation_length(mut self, simulation_length: u32) -> Self {
+        self.options.simulation_length = simulation_length;
+        self
+    }
+
+    pub fn herd_size(mut self, herd_size: u16) -> Self {
+        self.options.herd_size = herd_size;
+        self
+    }
+
+    pub fn tree_height(mut self, tree_height: u32) -> Self {
+        self.options.tree_height = tree_height;
+        self
+    }
+
+    pub fn build(self) -> Result<Options, OptionsError> {
+        let options = self.options;
+
+        if options.herd_size < MIN_HERD_SIZE {
+            return Err(OptionsError::OutOfRange {
+                option: "herd size".to_string(),
+                value:  options.herd_size as f64,
+                min:    MIN_HERD_SIZE as f64,
+                max:    ::std::u16::MAX as f64
+            });
+        }
+
+        let targets = vec![
+            ("tree height", options.tree_height),
+            ("lion speed", options.lion_speed),
+            ("color", options.color)
+        ];
+
+        if let Some(&(option, _)) = targets.iter().find(|&&(_, target)| target == 0) {
+            return Err(OptionsError::ZeroTarget { option: option });
+        }
+
+        if options.markers > giraffe::MAX_MARKERS {
+            return Err(OptionsError::OutOfRange {
+                option: "neutral markers".to_string(),
+                value:  options.markers as f64,
+                min:    0.0,
+                max:    giraffe::MAX_MARKERS as f64
+            });
+        }
+
+        if options.mate_candidates < 1 || options.mate_candidates > MAX_MATE_CANDIDATES {
+            return Err(OptionsError::OutOfRange {
+                option: "mate candidates".to_string(),
+                value:  options.mate_candidates as f64,
+                min:    1.0,
+                max:    MAX_MATE_CANDIDATES as f64
+            });
+        }
+
+        let proportions = vec![
+            ("mutation rate", options.mutation_rate),
+            ("competition strength", options.competition_strength)
+        ];
+
+        for (option, value) in proportions {
+            if !(value >= 0.0 && value <= 1.0) {
+                return Err(OptionsError::OutOfRange { option: option.to_string(), value: value as f64, min: 0.0, max: 1.0 });
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use docopt;
+
+    use blending::BlendingMode;
+    use canopy::ForageModel;
+    use competition::Competition;
+    use giraffe;
+    use mating::{Inbreeding, Mating, MatingTrait};
+    use mutation::MutationDecay;
+    use output::ImageFormat;
+    use reporter::LogFormat;
+    use selection::Selection;
+    use terrain::Phenotype;
+
+    use super::*;
+
+    fn args(argv: &[&str]) -> ::Args {
+        docopt::Docopt::new(::USAGE)
+            .and_then(|d| d.argv(argv.iter()).decode())
+            .unwrap()
+    }
+
+    fn variant<T>(result: Result<T, OptionsError>) -> &'static str {
+        match result {
+            Ok(_)                                    => panic!("expected an error"),
+            Err(OptionsError::UnknownValue { .. })   => "UnknownValue",
+            Err(OptionsError::MalformedValue { .. }) => "MalformedValue",
+            Err(OptionsError::OutOfRange { .. })     => "OutOfRange",
+            Err(OptionsError::ZeroTarget { .. })     => "ZeroTarget",
+            Err(OptionsError::SameAxes { .. })       => "SameAxes",
+            Err(OptionsError::InvalidConfig { .. })  => "InvalidConfig"
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_unknown_values() {
+        assert_eq!(variant(::parse_name("--mutdecay", "cubic", "", MutationDecay::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--blendmode", "max", "", BlendingMode::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--forage", "grass", "", ForageModel::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--competition", "war", "", Competition::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--selection", "sexual", "", Selection::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--mating", "harem", "", Mating::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--matetrait", "speed", "", MatingTrait::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--inbreeding", "prefer", "", Inbreeding::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--xaxis", "tail", "", Phenotype::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--log-format", "xml", "", LogFormat::from_name)), "UnknownValue");
+        assert_eq!(variant(::parse_name("--format", "gif", "", ImageFormat::from_name)), "UnknownValue");
+        assert_eq!(variant(::make_output_from_args(&args(&["giraffe", "--figures=color,spots"]))), "UnknownValue");
+    }
+
+    #[test]
+    fn unparseable_values_are_malformed() {
+        assert_eq!(variant(::parse_number::<u32>("--length", "long")), "MalformedValue");
+        assert_eq!(variant(::parse_number::<u16>("--size", "-5")), "MalformedValue");
+        assert_eq!(variant(::parse_percentage("--mutrate", "half")), "MalformedValue");
+        assert_eq!(variant(::make_terrain_spec_from_args(&args(&["giraffe", "--xrange=9:1"]))), "MalformedValue");
+        assert_eq!(variant(::make_terrain_spec_from_args(&args(&["giraffe", "--fix=color"]))), "MalformedValue");
+        assert_eq!(variant(::make_output_from_args(&args(&["giraffe", "--imagesize=big"]))), "MalformedValue");
+    }
+
+    #[test]
+    fn values_beyond_their_limits_are_out_of_range() {
+        assert_eq!(variant(::parse_percentage("--mutrate", "101")), "OutOfRange");
+        assert_eq!(variant(::parse_percentage("--compstrength", "-1")), "OutOfRange");
+        assert_eq!(variant(::make_terrain_spec_from_args(&args(&["giraffe", "--resolution=1"]))), "OutOfRange");
+        assert_eq!(variant(::make_frames_from_args(&args(&["giraffe", "--frames=0"]))), "OutOfRange");
+        assert_eq!(variant(::make_boxplots_from_args(&args(&["giraffe", "--boxplots=0"]))), "OutOfRange");
+        assert_eq!(variant(::make_terrain_spec_from_args(&args(&["giraffe", "--xrange=0:99999"]))), "OutOfRange");
+        assert_eq!(variant(OptionsBuilder::new().markers(giraffe::MAX_MARKERS + 1).build()), "OutOfRange");
+        assert_eq!(variant(OptionsBuilder::new().mate_candidates(0).build()), "OutOfRange");
+        assert_eq!(variant(OptionsBuilder::new().mate_candidates(MAX_MATE_CANDIDATES + 1).build()), "OutOfRange");
+        assert_eq!(variant(OptionsBuilder::new().mutation_rate(1.5).build()), "OutOfRange");
+        assert_eq!(variant(OptionsBuilder::new().competition_strength(-0.5).build()), "OutOfRange");
+    }
+
+    #[test]
+    fn zero_targets_are_rejected() {
+        assert_eq!(OptionsBuilder::new().tree_height(0).build().unwrap_err(), OptionsError::ZeroTarget { option: "tree height" });
+        assert_eq!(OptionsBuilder::new().lion_speed(0).build().unwrap_err(), OptionsError::ZeroTarget { option: "lion speed" });
+        assert_eq!(OptionsBuilder::new().color(0).build().unwrap_err(), OptionsError::ZeroTarget { option: "color" });
+    }
+
+    #[test]
+    fn an_empty_herd_is_rejected() {
+        assert_eq!(variant(OptionsBuilder::new().herd_size(0).build()), "OutOfRange");
+        assert_eq!(variant(OptionsBuilder::new().herd_size(1).build()), "OutOfRange");
+        assert!(OptionsBuilder::new().herd_size(MIN_HERD_SIZE).build().is_ok());
+    }
+
+    #[test]
+    fn matching_terrain_axes_are_rejected() {
+        assert_eq!(
+            ::make_terrain_spec_from_args(&args(&["giraffe", "terrain", "plot", "--xaxis=neck", "--yaxis=neck"])).unwrap_err(),
+            OptionsError::SameAxes { phenotype: "neck" }
+        );
+    }
+
+    #[test]
+    fn the_defaults_build() {
+        assert!(OptionsBuilder::new().build().is_ok());
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use rustc_serialize::json;
+
+use defaults;
+use options::Options;
+
+
+// Figures plotted after a simulation when no --figures are chosen.
+const DEFAULT_FIGURES: [&'static str; 5] = ["color", "height", "speed", "fitness", "dashboard"];
+
+const FIGURES: [&'static str; 15] = [
+    "color", "height", "speed", "fitness", "dashboard", "diversity", "color_histogram",
+    "height_histogram", "speed_histogram", "lineages", "loci", "allele_frequencies", "drift", "clusters",
+    "distributions"
+];
+
+pub const FIGURE_NAMES: &'static str = "color | height | speed | fitness | dashboard | diversity | \
+                                        color_histogram | height_histogram | speed_histogram | lineages | loci | \
+                                        allele_frequencies | drift | clusters | distributions";
+
+#[derive(Copy, Clone, Debug)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+    Pdf
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<ImageFormat> {
+        match name {
+            "png" => Some(ImageFormat::Png),
+            "svg" => Some(ImageFormat::Svg),
+            "pdf" => Some(ImageFormat::Pdf),
+            _     => None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+            ImageFormat::Pdf => "pdf"
+        }
+    }
+
+    // pdfcairo sizes are in inches, so pixels are taken at 100 per inch.
+    pub fn terminal(&self, width: u32, height: u32) -> String {
+        match *self {
+            ImageFormat::Png => format!("png size {},{}", width, height),
+            ImageFormat::Svg => format!("svg size {},{}", width, height),
+            ImageFormat::Pdf => format!("pdfcairo size {:.2}in,{:.2}in", width as f32 / 100.0, height as f32 / 100.0)
+        }
+    }
+}
+
+#[derive(RustcEncodable)]
+struct Manifest {
+    name:    Option<String>,
+    options: Options,
+    files:   Vec<String>
+}
+
+// Where and how generated files are written. Every path handed out is
+// recorded so the run's manifest can list it.
+pub struct Output {
+    pub directory: String,
+    pub name:      Option<String>,
+    pub format:    ImageFormat,
+    pub width:     u32,
+    pub height:    u32,
+    pub figures:   Option<Vec<String>>,
+    files:         Vec<String>
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Output {
+            directory: defaults::OUTPUT_DIRECTORY.to_string(),
+            name:      None,
+            format:    defaults::IMAGE_FORMAT,
+            width:     defaults::IMAGE_WIDTH,
+            height:    defaults::IMAGE_HEIGHT,
+            figures:   None,
+            files:     vec![]
+        }
+    }
+}
+
+impl Output {
+    pub fn create_directory(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)
+    }
+
+    pub fn path(&self, file_name: &str) -> String {
+        let file_name = match self.name {
+            Some(ref name) => format!("{}_{}", name, file_name),
+            None           => file_name.to_string()
+        };
+
+        Path::new(&self.directory).join(file_name).to_string_lossy().into_owned()
+    }
+
+    pub fn file(&mut self, file_name: &str) -> String {
+        let path = self.path(file_name);
+
+        self.files.push(path.clone());
+
+        path
+    }
+
+    pub fn image(&mut self, name: &str) -> String {
+        let file_name = format!("{}.{}", name, self.format.extension());
+
+        self.file(&file_name)
+    }
+
+    // The destination of a simulation figure, or None when it wasn't selected.
+    // Figures asked for by their own flag are plotted either way.
+    pub fn figure(&mut self, name: &str, is_requested: bool) -> Option<String> {
+        if is_requested || self.is_selected(name) {
+            Some(self.image(&format!("{}_output", name)))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_selected(&self, name: &str) -> bool {
+        match self.figures {
+            Some(ref figures) => figures.iter().any(|figure| figure == name),
+            None              => DEFAULT_FIGURES.contains(&name)
+        }
+    }
+
+    pub fn terminal(&self) -> String {
+        self.format.terminal(self.width, self.height)
+    }
+
+    pub fn write_manifest(&self, options: &Options) -> io::Result<String> {
+        let destination = self.path("manifest.json");
+        let mut file    = File::create(&destination)?;
+
+        let manifest = Manifest {
+            name:    self.name.clone(),
+            options: options.clone(),
+            files:   self.files.clone()
+        };
+
+        write!(file, "{}", json::as_pretty_json(&manifest))?;
+
+        Ok(destination)
+    }
+}
+
+pub fn is_figure(name: &str) -> bool {
+    FIGURES.contains(&name)
+}
+
+pub fn parse_image_size(size: &str) -> Option<(u32, u32)> {
+    let dimensions = size.split('x').map(|dimension| {
+        dimension.trim().parse::<u32>().ok()
+    }).collect::<Vec<Option<u32>>>();
+
+    if dimensions.len() != 2 {
+        return None;
+    }
+
+    match (dimensions[0], dimensions[1]) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => Some((width, height)),
+        _                                                      => None
+    }
+}
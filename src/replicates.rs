@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use giraffe_lib::quantile;
+use statistics::Statistics;
+
+
+pub struct Band {
+    pub mean:   Vec<f64>,
+    pub median: Vec<f64>,
+    pub lower:  Vec<f64>,
+    pub upper:  Vec<f64>
+}
+
+// Each statistics series summarized per generation across independent runs of
+// the same options, so a single lucky trajectory stands out against the band.
+pub struct Replicates {
+    pub count: usize,
+    bands:     Vec<(&'static str, Band)>
+}
+
+impl Replicates {
+    pub fn new(runs: &Vec<&Statistics>) -> Self {
+        let all_series = runs.iter().map(|statistics| statistics.series()).collect::<Vec<Vec<(&'static str, Vec<f64>)>>>();
+
+        let bands = all_series[0].iter().enumerate().map(|(index, &(name, _))| {
+            let runs_series = all_series.iter().map(|series| &series[index].1).collect::<Vec<&Vec<f64>>>();
+
+            (name, band(&runs_series))
+        }).collect();
+
+        Replicates {
+            count: runs.len(),
+            bands: bands
+        }
+    }
+
+    pub fn band(&self, series: &str) -> Option<&Band> {
+        self.bands.iter().find(|&&(name, _)| name == series).map(|&(_, ref band)| band)
+    }
+
+    pub fn export_csv(&self, destination_file: &str) -> io::Result<()> {
+        let mut file = File::create(destination_file)?;
+
+        let mut header = vec!["generation".to_string(), "runs".to_string()];
+
+        for &(name, _) in &self.bands {
+            header.push(format!("{}_mean", name));
+            header.push(format!("{}_median", name));
+            header.push(format!("{}_p5", name));
+            header.push(format!("{}_p95", name));
+        }
+
+        writeln!(file, "{}", header.join(","))?;
+
+        let generations = self.bands.first().map_or(0, |&(_, ref band)| band.mean.len());
+
+        for i in 0..generations {
+            let mut row = vec![i.to_string(), self.count.to_string()];
+
+            for &(_, ref band) in &self.bands {
+                row.push(band.mean[i].to_string());
+                row.push(band.median[i].to_string());
+                row.push(band.lower[i].to_string());
+                row.push(band.upper[i].to_string());
+            }
+
+            writeln!(file, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Runs that stopped early only count towards the generations they reached.
+fn band(runs: &Vec<&Vec<f64>>) -> Band {
+    let generations = runs.iter().map(|values| values.len()).max().unwrap_or(0);
+
+    let mut result = Band { mean: vec![], median: vec![], lower: vec![], upper: vec![] };
+
+    for i in 0..generations {
+        let mut values = runs.iter().filter_map(|values| values.get(i).cloned()).collect::<Vec<f64>>();
+
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        result.mean.push(values.iter().fold(0.0, |acc, value| acc + value) / values.len() as f64);
+        result.median.push(quantile(&values, 0.5));
+        result.lower.push(quantile(&values, 0.05));
+        result.upper.push(quantile(&values, 0.95));
+    }
+
+    result
+}
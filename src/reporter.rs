@@ -0,0 +1,264 @@
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use rustc_serialize::Encodable;
+use rustc_serialize::json;
+
+use statistics::{Snapshot, Statistics};
+
+
+const SPARKLINE_WIDTH:    usize = 48;
+const PROGRESS_WIDTH:     usize = 30;
+const RECENT_EVENTS:      usize = 3;
+const REDRAW_INTERVAL_MS: u64   = 50;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const HIGHLIGHT: &'static str = "\x1b[1;33m";
+const DIM:       &'static str = "\x1b[2m";
+const RESET:     &'static str = "\x1b[0m";
+
+#[derive(Copy, Clone, Debug)]
+pub enum LogFormat {
+    Tui,
+    Text,
+    Json,
+    Quiet
+}
+
+impl LogFormat {
+    pub fn from_name(name: &str) -> Option<LogFormat> {
+        match name {
+            "tui"  => Some(LogFormat::Tui),
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _      => None
+        }
+    }
+
+    // The live dashboard when stdout is a terminal, plain lines when it's
+    // piped or redirected.
+    pub fn default_format() -> LogFormat {
+        if io::stdout().is_terminal() { LogFormat::Tui } else { LogFormat::Text }
+    }
+
+    // Where everything besides the reports goes. JSON lines and quiet runs
+    // keep stdout for the reports alone.
+    pub fn notes(&self) -> Box<dyn Write> {
+        match *self {
+            LogFormat::Tui | LogFormat::Text   => Box::new(io::stdout()),
+            LogFormat::Json | LogFormat::Quiet => Box::new(io::stderr())
+        }
+    }
+}
+
+// Reports the simulation's progress once per generation in the chosen format.
+// The tui format redraws a fixed block of lines in place.
+pub struct Reporter {
+    format:      LogFormat,
+    length:      u32,
+    started:     Instant,
+    last_drawn:  Option<Instant>,
+    drawn:       Option<u32>,
+    lines_drawn: usize,
+    events:      Vec<String>
+}
+
+impl Reporter {
+    pub fn new(format: LogFormat, length: u32) -> Self {
+        Reporter {
+            format:      format,
+            length:      length,
+            started:     Instant::now(),
+            last_drawn:  None,
+            drawn:       None,
+            lines_drawn: 0,
+            events:      vec![]
+        }
+    }
+
+    pub fn report(&mut self, statistics: &Statistics) {
+        let latest = match statistics.latest() {
+            Some(latest) => latest,
+            None         => return
+        };
+
+        if latest.environment_changed {
+            self.events.push(describe_change(&statistics, &latest));
+        }
+
+        match self.format {
+            LogFormat::Tui   => self.draw(&statistics, &latest),
+            LogFormat::Text  => self.print_text(&statistics, &latest),
+            LogFormat::Json  => println!("{}", json::encode(&latest).expect("Failed to encode statistics")),
+            LogFormat::Quiet => {}
+        }
+    }
+
+    // Draws the last generation if the redraw interval skipped it.
+    pub fn finish(&mut self, statistics: &Statistics) {
+        if let LogFormat::Tui = self.format {
+            if let Some(latest) = statistics.latest() {
+                if self.drawn != Some(latest.generation) {
+                    self.last_drawn = None;
+                    self.draw(&statistics, &latest);
+                }
+            }
+        }
+    }
+
+    // The run's summary as a final JSON line, after the per-generation ones.
+    pub fn report_summary<T: Encodable>(&self, summary: &T) {
+        if let LogFormat::Json = self.format {
+            println!("{}", json::encode(summary).expect("Failed to encode summary"));
+        }
+    }
+
+    // Changes are only highlighted on a terminal, so piped lines stay plain.
+    fn print_text(&self, statistics: &Statistics, latest: &Snapshot) {
+        if latest.environment_changed {
+            let event = self.events.last().unwrap();
+
+            if io::stdout().is_terminal() {
+                println!("{}{}{}", HIGHLIGHT, event, RESET);
+            } else {
+                println!("{}", event);
+            }
+        }
+
+        statistics.print_latest();
+    }
+
+    fn generations_per_second(&self, generation: u32) -> f64 {
+        let elapsed = seconds(self.started.elapsed());
+
+        if elapsed > 0.0 { generation as f64 / elapsed } else { 0.0 }
+    }
+
+    fn draw(&mut self, statistics: &Statistics, latest: &Snapshot) {
+        let is_finished = latest.generation >= self.length;
+        let is_due      = self.last_drawn.map_or(true, |drawn| {
+            drawn.elapsed() >= Duration::from_millis(REDRAW_INTERVAL_MS)
+        });
+
+        if !is_due && !is_finished && !latest.environment_changed {
+            return;
+        }
+
+        let rate       = self.generations_per_second(latest.generation);
+        let remaining  = self.length.saturating_sub(latest.generation);
+        let proportion = if self.length > 0 { latest.generation as f64 / self.length as f64 } else { 1.0 };
+        let filled     = ((proportion * PROGRESS_WIDTH as f64).round() as usize).min(PROGRESS_WIDTH);
+
+        let mut lines = vec![
+            format!(
+                "Generation {:>6}/{} [{}{}] {:>5.1}%  {:>7.1} gen/s  ETA {}",
+                latest.generation,
+                self.length,
+                "#".repeat(filled),
+                "-".repeat(PROGRESS_WIDTH - filled),
+                proportion * 100.0,
+                rate,
+                if rate > 0.0 { format_duration(remaining as f64 / rate) } else { "--".to_string() }
+            ),
+            String::new()
+        ];
+
+        for (label, means, targets) in statistics.trait_histories() {
+            let targets = targets.iter().map(|&target| target as f64).collect::<Vec<f64>>();
+            let (minimum, maximum) = bounds(&means, &targets);
+
+            lines.push(format!(
+                "{:<7} mean   {} {:>8.1}",
+                label, sparkline(&means, minimum, maximum), means.last().cloned().unwrap_or(0.0)
+            ));
+            lines.push(format!(
+                "{}        target {} {:>8.0}{}",
+                DIM, sparkline(&targets, minimum, maximum), targets.last().cloned().unwrap_or(0.0), RESET
+            ));
+        }
+
+        lines.push(format!(
+            "Fitness mean {:.3}, maximum {:.3}",
+            latest.fitness_mean, latest.fitness_maximum
+        ));
+        lines.push(String::new());
+
+        let recent = self.events.len().saturating_sub(RECENT_EVENTS);
+
+        for i in 0..RECENT_EVENTS {
+            lines.push(match self.events.get(recent + i) {
+                Some(event) => format!("{}{}{}", HIGHLIGHT, event, RESET),
+                None        => String::new()
+            });
+        }
+
+        let stdout     = io::stdout();
+        let mut handle = stdout.lock();
+
+        if self.lines_drawn > 0 {
+            let _ = write!(handle, "\x1b[{}A", self.lines_drawn);
+        }
+
+        for line in &lines {
+            let _ = writeln!(handle, "\x1b[2K{}", line);
+        }
+
+        let _ = handle.flush();
+
+        self.lines_drawn = lines.len();
+        self.last_drawn  = Some(Instant::now());
+        self.drawn       = Some(latest.generation);
+    }
+}
+
+fn describe_change(statistics: &Statistics, latest: &Snapshot) -> String {
+    let changes = statistics.trait_histories().into_iter().filter_map(|(label, _, targets)| {
+        let n = targets.len();
+
+        if n > 1 && targets[n - 1] != targets[n - 2] {
+            Some(format!("{} target {} -> {}", label.to_lowercase(), targets[n - 2], targets[n - 1]))
+        } else {
+            None
+        }
+    }).collect::<Vec<String>>();
+
+    format!("Generation {}: {}", latest.generation, changes.join(", "))
+}
+
+fn bounds(means: &Vec<f64>, targets: &Vec<f64>) -> (f64, f64) {
+    means.iter().chain(targets.iter()).fold((::std::f64::MAX, ::std::f64::MIN), |(minimum, maximum), &value| {
+        (minimum.min(value), maximum.max(value))
+    })
+}
+
+// The whole history squeezed into SPARKLINE_WIDTH columns, each the mean of
+// the generations it covers.
+fn sparkline(values: &Vec<f64>, minimum: f64, maximum: f64) -> String {
+    let columns = SPARKLINE_WIDTH.min(values.len());
+    let spread  = maximum - minimum;
+
+    (0..columns).map(|column| {
+        let start = column * values.len() / columns;
+        let end   = ((column + 1) * values.len() / columns).max(start + 1);
+        let mean  = values[start..end].iter().fold(0.0, |acc, value| acc + value) / (end - start) as f64;
+        let level = if spread > 0.0 { (mean - minimum) / spread } else { 0.5 };
+
+        SPARKS[((level * (SPARKS.len() - 1) as f64).round() as usize).min(SPARKS.len() - 1)]
+    }).collect::<String>()
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1e9
+}
+
+fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+
+    if seconds >= 60 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
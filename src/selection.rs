@@ -0,0 +1,31 @@
+#[derive(Copy, Clone, Debug, PartialEq, RustcEncodable)]
+pub enum Selection {
+    Natural,
+    Neutral
+}
+
+impl Selection {
+    pub fn from_name(name: &str) -> Option<Selection> {
+        match name {
+            "natural" => Some(Selection::Natural),
+            "neutral" => Some(Selection::Neutral),
+            _         => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Selection::Natural => "natural",
+            Selection::Neutral => "neutral"
+        }
+    }
+}
+
+// Under neutral selection every giraffe is equally fit, so parents are drawn
+// uniformly and only drift moves the gene pool.
+pub fn apply_selection(selection: Selection, fitnesses: Vec<f32>) -> Vec<f32> {
+    match selection {
+        Selection::Natural => fitnesses,
+        Selection::Neutral => fitnesses.iter().map(|_| 1.0).collect()
+    }
+}
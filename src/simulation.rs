@@ -0,0 +1,127 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use convergence::Convergence;
+use giraffe_lib;
+use options::Options;
+use statistics::Statistics;
+use termination::{Reason, Rules, Termination};
+use world::World;
+
+
+// Evolves a random tower for the whole simulation length, handing every
+// generation, the first included, to observe once its statistics are in.
+// The run stops early if observe returns false.
+pub fn simulate<F>(options: &Options, mut observe: F) -> Statistics where F: FnMut(&World, &Statistics) -> bool {
+    let mut statistics = Statistics::new();
+    let mut world      = World::new(options.clone());
+
+    statistics.update(&world);
+
+    if !observe(&world, &statistics) {
+        return statistics;
+    }
+
+    for _ in 0..options.simulation_length {
+        world = world.evolve();
+        statistics.update(&world);
+
+        if !observe(&world, &statistics) {
+            break;
+        }
+    }
+
+    statistics
+}
+
+// Simulates until the run completes or one of the rules stops it, recording
+// why it ended.
+pub fn simulate_until<F>(options: &Options, rules: Rules, mut observe: F) -> Statistics
+    where F: FnMut(&World, &Statistics)
+{
+    let mut termination = Termination::new(rules);
+    let mut reason      = Reason::Completed;
+
+    let mut statistics = simulate(&options, |world, statistics| {
+        observe(&world, &statistics);
+
+        match termination.check(&statistics) {
+            Some(stop) => {
+                reason = stop;
+                false
+            },
+            None => true
+        }
+    });
+
+    statistics.set_termination(reason);
+    statistics
+}
+
+// What a run ended with, written to summary.json.
+#[derive(RustcEncodable)]
+pub struct Summary {
+    pub seed:        u64,
+    pub generations: u32,
+    pub termination: String,
+    pub convergence: Convergence
+}
+
+pub struct Job {
+    pub options: Options,
+    pub rules:   Rules,
+    pub seed:    u64
+}
+
+// Simulates every job on a pool of worker threads, each run seeded with its
+// job's seed, and returns what summarize makes of each run in the jobs' order.
+// on_finished is handed each result as it arrives.
+pub fn simulate_all<T, F, G>(jobs: Vec<Job>, threads: usize, summarize: F, mut on_finished: G) -> Vec<T>
+    where T: Send + 'static,
+          F: Fn(Statistics) -> T + Send + Sync + 'static,
+          G: FnMut(usize, &T)
+{
+    let total             = jobs.len();
+    let summarize         = Arc::new(summarize);
+    let pending           = Arc::new(Mutex::new(jobs.into_iter().enumerate().rev().collect::<Vec<(usize, Job)>>()));
+    let (sender, results) = mpsc::channel();
+
+    let workers = (0..threads.max(1).min(total)).map(|_| {
+        let pending   = pending.clone();
+        let summarize = summarize.clone();
+        let sender    = sender.clone();
+
+        thread::spawn(move || {
+            loop {
+                let next = pending.lock().unwrap().pop();
+
+                let (index, job) = match next {
+                    Some(next) => next,
+                    None       => break
+                };
+
+                giraffe_lib::seed_random(job.seed);
+
+                let statistics = simulate_until(&job.options, job.rules, |_, _| {});
+
+                sender.send((index, summarize(statistics))).unwrap();
+            }
+        })
+    }).collect::<Vec<thread::JoinHandle<()>>>();
+
+    drop(sender);
+
+    let mut finished = vec![];
+
+    for (index, result) in results.iter() {
+        on_finished(finished.len() + 1, &result);
+        finished.push((index, result));
+    }
+
+    for worker in workers {
+        worker.join().expect("Simulation worker panicked");
+    }
+
+    finished.sort_by_key(|&(index, _)| index);
+    finished.into_iter().map(|(_, result)| result).collect()
+}
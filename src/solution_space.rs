@@ -3,99 +3,103 @@ use gnuplot::AxesCommon;
 use nalgebra;
 use kiss3d;
 
+use std::f32;
+use std::fs;
+use std::io;
 use std::rc::Rc;
 use std::cell::RefCell;
 
 use giraffe::Giraffe;
 use options::Options;
+use terrain;
+use terrain::TerrainSpec;
 use world;
 
 
-const NUM_ROWS: u32 = (!0 as u8) as u32 * 4;
-const NUM_COLS: u32 = (!0 as u8) as u32 * 8;
+const OVERLAY_SAMPLE_SIZE: usize = 200;
+const OVERLAY_SPHERE_SIZE: f32   = 0.05;
+const GRADIENT_BANDS:      usize = 12;
 
-fn create_test_tower() -> Vec<Giraffe> {
-    let mut results = vec![];
+// World units per unit of fitness along the vertical axis of the 3d terrain;
+// the horizontal axes use one unit per 100 of a trait.
+pub const HEIGHT_SCALE: f32 = 4.0;
 
-    for legs_size in 0..NUM_ROWS {
-        for neck_size in 0..NUM_COLS {
-            let giraffe = Giraffe::new_from_phenotypic_values(
-                0, legs_size, neck_size
-            );
-            results.push(giraffe);
-        }
-    }
-
-    results
+// A fitness matrix that follows the world's tree height, morphing whenever
+// the environment changes.
+pub struct Landscape {
+    spec:        TerrainSpec,
+    tree_height: u32,
+    pub matrix:  Vec<Vec<f32>>
 }
 
-fn create_test_world(options: Options) -> world::World {
-    let tower = create_test_tower();
-
-    world::World::new_from_tower(tower, options)
-}
-
-fn create_fitness_matrix(options: Options) -> Vec<Vec<f32>> {
-    let world     = create_test_world(options);
-    let fitnesses = world::calculate_fitnesses(&world, &world.tower);
-
-    let mut results = vec![];
+impl Landscape {
+    pub fn new(spec: &TerrainSpec, options: &Options) -> Self {
+        Landscape {
+            spec:        *spec,
+            tree_height: options.tree_height,
+            matrix:      terrain::create_fitness_matrix(&spec, &options)
+        }
+    }
 
-    for row_index in 0..NUM_ROWS {
-        let start_value = row_index * NUM_COLS;
+    pub fn track(&mut self, options: &Options) -> bool {
+        if options.tree_height == self.tree_height {
+            return false;
+        }
 
-        let mut row = vec![];
+        *self = Landscape::new(&self.spec, &options);
 
-        for col_index in 0..NUM_COLS {
-            let flat_index = (start_value + col_index) as usize;
+        true
+    }
 
-            row.push(fitnesses[flat_index]);
-        }
+    fn fitness_at(&self, giraffe: &Giraffe) -> f32 {
+        let (row, col) = self.spec.cell_of(&giraffe);
 
-        results.push(row);
+        self.matrix[row][col]
     }
-
-    results
 }
 
-fn create_mesh_points(matrix: &Vec<Vec<f32>>) -> Vec<nalgebra::Point3<f32>> {
+pub fn create_mesh_points(spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> Vec<nalgebra::Point3<f32>> {
+    let x_values = spec.x_values();
+    let y_values = spec.y_values();
+
     let mut results = vec![];
 
-    for i in 0..(NUM_ROWS as usize) {
-        for j in 0..(NUM_COLS as usize) {
-            results.push(
-                nalgebra::Point3::new(
-                    i as f32 / 100.0,
-                    j as f32 / 100.0,
-                    (matrix[i][j] * 100.0).sqrt()
-                )
-            )
+    for i in 0..spec.resolution {
+        for j in 0..spec.resolution {
+            results.push(create_mesh_point(y_values[i] as f32, x_values[j] as f32, matrix[i][j]))
         }
     }
 
     results
 }
 
-fn create_mesh_triangles() -> Vec<nalgebra::Point3<u32>> {
+pub fn create_mesh_point(row_value: f32, col_value: f32, fitness: f32) -> nalgebra::Point3<f32> {
+    nalgebra::Point3::new(row_value / 100.0, col_value / 100.0, fitness * HEIGHT_SCALE)
+}
+
+pub fn create_mesh_triangles(spec: &TerrainSpec) -> Vec<nalgebra::Point3<u32>> {
+    let num_rows = spec.resolution as u32;
+    let num_cols = spec.resolution as u32;
+
     let mut results = vec![];
 
-    for i in 0..(NUM_ROWS - 1) {
-        let start_value = i * NUM_COLS;
+    for i in 0..(num_rows - 1) {
+        let start_value = i * num_cols;
 
-        for j in 0..(NUM_COLS - 1) {
+        for j in 0..(num_cols - 1) {
             results.push(
                 nalgebra::Point3::new(
                     j + start_value,
                     j + 1 + start_value,
-                    j + start_value + NUM_COLS
+                    j + start_value + num_cols
                 )
             );
 
             results.push(
                 nalgebra::Point3::new(
                     j + 1 + start_value,
-                    j + 1 + start_value + NUM_COLS,
-                    j + start_value + NUM_COLS
+                    j + 1 + start_value + num_cols,
+                    j + start_value + num_cols
                 )
             );
         }
@@ -104,45 +108,228 @@ fn create_mesh_triangles() -> Vec<nalgebra::Point3<u32>> {
     results
 }
 
-pub fn render_plot(destination: &str, options: Options) {
-    let world     = create_test_world(options);
-    let fitnesses = world::calculate_fitnesses(&world, &world.tower);
+fn dimensions(spec: &TerrainSpec) -> Option<(f64, f64, f64, f64)> {
+    Some((
+        spec.x_axis.min as f64,
+        spec.y_axis.min as f64,
+        spec.x_axis.max as f64,
+        spec.y_axis.max as f64
+    ))
+}
+
+pub fn render_plot(terminal: &str, destination: &str, spec: &TerrainSpec, options: Options) {
+    let fitnesses = terrain::create_fitness_matrix(&spec, &options).concat();
 
     let mut figure = gnuplot::Figure::new();
 
-    figure.set_terminal("png", destination);
+    figure.set_terminal(terminal, destination);
     figure.axes3d()
-        .set_x_label("Neck Length", &vec![])
-        .set_y_label("Leg Length", &vec![])
+        .set_x_label(spec.x_axis.phenotype.label(), &vec![])
+        .set_y_label(spec.y_axis.phenotype.label(), &vec![])
         .set_z_label("Fitness", &vec![])
-        .surface(&fitnesses, NUM_ROWS as usize, NUM_COLS as usize, None, &vec![
+        .surface(&fitnesses, spec.resolution, spec.resolution, dimensions(&spec), &vec![
+            gnuplot::Caption("Fitness Terrain")
+        ]);
+
+    figure.show();
+}
+
+pub fn render_contour(terminal: &str, destination: &str, spec: &TerrainSpec, options: Options) {
+    let fitnesses = terrain::create_fitness_matrix(&spec, &options).concat();
+
+    let mut figure = gnuplot::Figure::new();
+
+    figure.set_terminal(terminal, destination);
+    figure.axes3d()
+        .set_x_label(spec.x_axis.phenotype.label(), &vec![])
+        .set_y_label(spec.y_axis.phenotype.label(), &vec![])
+        .set_view_map()
+        .show_contours(true, false, gnuplot::ContourStyle::Linear, gnuplot::Auto, gnuplot::Fix(10))
+        .surface(&fitnesses, spec.resolution, spec.resolution, dimensions(&spec), &vec![
             gnuplot::Caption("Fitness Terrain")
         ]);
 
     figure.show();
 }
 
-pub fn render_3d(options: Options) {
-    let mut window = kiss3d::window::Window::new("Fitness Terrain");
+pub fn render_heatmap(terminal: &str, destination: &str, spec: &TerrainSpec, options: Options) {
+    let fitnesses = terrain::create_fitness_matrix(&spec, &options).concat();
 
-    let matrix    = create_fitness_matrix(options);
-    let vertices  = create_mesh_points(&matrix);
-    let triangles = create_mesh_triangles();
+    let mut figure = gnuplot::Figure::new();
+
+    figure.set_terminal(terminal, destination);
+    figure.axes2d()
+        .set_x_label(spec.x_axis.phenotype.label(), &vec![])
+        .set_y_label(spec.y_axis.phenotype.label(), &vec![])
+        .set_cb_label("Fitness", &vec![])
+        .image(&fitnesses, spec.resolution, spec.resolution, dimensions(&spec), &vec![]);
+
+    figure.show();
+}
+
+// Blue for the lowest fitness, through green, to red for the highest.
+pub fn gradient_color(proportion: f32) -> (f32, f32, f32) {
+    let proportion = proportion.max(0.0).min(1.0);
+
+    if proportion < 0.5 {
+        (0.0, proportion * 2.0, 1.0 - proportion * 2.0)
+    } else {
+        ((proportion - 0.5) * 2.0, 1.0 - (proportion - 0.5) * 2.0, 0.0)
+    }
+}
+
+// The lowest fitness and the spread up to the highest, for mapping onto the gradient.
+pub fn fitness_range(fitnesses: &Vec<f32>) -> (f32, f32) {
+    let minimum = fitnesses.iter().fold(f32::MAX, |acc, &fitness| acc.min(fitness));
+    let maximum = fitnesses.iter().fold(f32::MIN, |acc, &fitness| acc.max(fitness));
+
+    (minimum, (maximum - minimum).max(f32::EPSILON))
+}
 
-    let mesh = Rc::new(
-        RefCell::new(
-            kiss3d::resource::Mesh::new(vertices, triangles, None, None, false)
-        )
-    );
+// kiss3d colors whole nodes, so the terrain is split into one mesh per band of
+// fitness, each colored along the gradient.
+pub fn add_terrain(parent: &mut kiss3d::scene::SceneNode, spec: &TerrainSpec, matrix: &Vec<Vec<f32>>) -> kiss3d::scene::SceneNode {
+    let vertices  = create_mesh_points(&spec, &matrix);
+    let fitnesses = matrix.concat();
 
-    let mut c = window.add_mesh(mesh, nalgebra::one());
+    let (minimum, spread) = fitness_range(&fitnesses);
 
-    c.set_color(1.0, 0.0, 0.0);
-    c.enable_backface_culling(false);
+    let mut bands = vec![vec![]; GRADIENT_BANDS];
+
+    for triangle in create_mesh_triangles(&spec) {
+        let fitness = (
+            fitnesses[triangle.x as usize] + fitnesses[triangle.y as usize] + fitnesses[triangle.z as usize]
+        ) / 3.0;
+
+        let band = (((fitness - minimum) / spread) * GRADIENT_BANDS as f32) as usize;
+
+        bands[band.min(GRADIENT_BANDS - 1)].push(triangle);
+    }
+
+    let mut terrain = parent.add_group();
+
+    for (band, triangles) in bands.into_iter().enumerate().filter(|&(_, ref triangles)| !triangles.is_empty()) {
+        let mesh = Rc::new(
+            RefCell::new(
+                kiss3d::resource::Mesh::new(vertices.clone(), triangles, None, None, false)
+            )
+        );
+
+        let (r, g, b) = gradient_color((band as f32 + 0.5) / GRADIENT_BANDS as f32);
+        let mut node  = terrain.add_mesh(mesh, nalgebra::one());
+
+        node.set_color(r, g, b);
+        node.enable_backface_culling(false);
+    }
+
+    terrain
+}
+
+pub fn render_overlay_plots(
+    directory: &str,
+    interval:  u32,
+    spec:      &TerrainSpec,
+    options:   Options,
+    terminal:  &str,
+    extension: &str
+) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+
+    let mut world     = world::World::new(options);
+    let mut landscape = Landscape::new(&spec, &world.options);
+
+    loop {
+        landscape.track(&world.options);
+
+        if world.generation % interval == 0 {
+            let destination = format!("{}/overlay_{:05}.{}", directory, world.generation, extension);
+
+            render_overlay_plot(terminal, &destination, &landscape, &world);
+        }
+
+        if world.generation >= world.options.simulation_length {
+            return Ok(());
+        }
+
+        world = world.evolve();
+    }
+}
+
+fn render_overlay_plot(terminal: &str, destination: &str, landscape: &Landscape, world: &world::World) {
+    let spec      = &landscape.spec;
+    let fitnesses = landscape.matrix.concat();
+
+    let x_values = world.tower.iter().map(|giraffe| {
+        spec.x_axis.phenotype.value_of(&giraffe)
+    }).collect::<Vec<u32>>();
+
+    let y_values = world.tower.iter().map(|giraffe| {
+        spec.y_axis.phenotype.value_of(&giraffe)
+    }).collect::<Vec<u32>>();
+
+    let giraffe_fitnesses = world.tower.iter().map(|giraffe| {
+        landscape.fitness_at(&giraffe)
+    }).collect::<Vec<f32>>();
+
+    let mut figure = gnuplot::Figure::new();
+
+    figure.set_terminal(terminal, destination);
+    figure.axes3d()
+        .set_title(&format!("Generation {}", world.generation), &vec![])
+        .set_x_label(spec.x_axis.phenotype.label(), &vec![])
+        .set_y_label(spec.y_axis.phenotype.label(), &vec![])
+        .set_z_label("Fitness", &vec![])
+        .surface(&fitnesses, spec.resolution, spec.resolution, dimensions(&spec), &vec![
+            gnuplot::Caption("Fitness Terrain")
+        ])
+        .points(&x_values, &y_values, &giraffe_fitnesses, &vec![
+            gnuplot::Caption("Giraffes"),
+            gnuplot::Color("black"),
+            gnuplot::PointSymbol('O'),
+            gnuplot::PointSize(0.5)
+        ]);
+
+    figure.show();
+}
+
+pub fn render_overlay_3d(spec: &TerrainSpec, options: Options) {
+    let mut window = kiss3d::window::Window::new("Fitness Terrain Overlay");
+
+    let mut world     = world::World::new(options);
+    let mut landscape = Landscape::new(&spec, &world.options);
+
+    let mut group   = window.add_group();
+    let mut terrain = add_terrain(&mut group, &spec, &landscape.matrix);
+
+    let mut spheres = (0..OVERLAY_SAMPLE_SIZE.min(world.tower.len())).map(|_| {
+        let mut sphere = group.add_sphere(OVERLAY_SPHERE_SIZE);
+
+        sphere.set_color(0.0, 0.0, 0.0);
+        sphere
+    }).collect::<Vec<kiss3d::scene::SceneNode>>();
 
     window.set_light(kiss3d::light::Light::StickToCamera);
 
     while window.render() {
-        c.prepend_to_local_rotation(&nalgebra::Vector3::new(0.0f32, 0.0, 0.001));
+        if world.generation < world.options.simulation_length {
+            world = world.evolve();
+
+            if landscape.track(&world.options) {
+                terrain.unlink();
+                terrain = add_terrain(&mut group, &spec, &landscape.matrix);
+            }
+        }
+
+        for (sphere, giraffe) in spheres.iter_mut().zip(world.tower.iter()) {
+            let position = create_mesh_point(
+                spec.y_axis.phenotype.value_of(&giraffe) as f32,
+                spec.x_axis.phenotype.value_of(&giraffe) as f32,
+                landscape.fitness_at(&giraffe)
+            );
+
+            sphere.set_local_translation(nalgebra::Vector3::new(position.x, position.y, position.z));
+        }
+
+        group.prepend_to_local_rotation(&nalgebra::Vector3::new(0.0f32, 0.0, 0.001));
     }
 }
@@ -1,43 +1,133 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
 use gnuplot;
 use gnuplot::AxesCommon;
 use statistical;
 
+use distribution;
+use distribution::Distribution;
+use genetics;
+use giraffe_lib::quantile;
+use histogram;
+use loci::LocusHistory;
+use replicates::Replicates;
+use termination::Reason;
 use traits::HasColor;
 use traits::HasHeight;
 use traits::HasSpeed;
 use world;
 
 
+#[derive(Clone, Debug, RustcEncodable)]
+pub struct Snapshot {
+    pub generation:          u32,
+    pub color_mean:          f64,
+    pub world_color:         u32,
+    pub height_mean:         f64,
+    pub tree_height:         u32,
+    pub speed_mean:          f64,
+    pub lion_speed:          u32,
+    pub fitness_mean:        f64,
+    pub fitness_maximum:     f64,
+    pub allele_entropy_mean: f64,
+    pub environment_changed: bool
+}
+
+// The traits whose distribution is recorded, in order.
+pub const DISTRIBUTION_TRAITS: [&'static str; 5] = ["color", "height", "speed", "leg", "neck"];
+
+const TRAIT_COLORS: [&'static str; 5] = ["blue", "green", "red", "orange", "purple"];
+
 pub struct Statistics {
-    color_means:           Vec<f64>,
-    color_std_deviations:  Vec<f64>,
-    height_means:          Vec<f64>,
-    height_std_deviations: Vec<f64>,
-    leg_means:             Vec<f64>,
-    neck_means:            Vec<f64>,
-    speed_means:           Vec<f64>,
-    speed_std_deviations:  Vec<f64>,
-    world_colors:          Vec<u32>,
-    lion_speeds:           Vec<u32>,
-    tree_heights:          Vec<u32>,
-    generations:           u32
+    color_means:                    Vec<f64>,
+    color_std_deviations:           Vec<f64>,
+    height_means:                   Vec<f64>,
+    height_std_deviations:          Vec<f64>,
+    leg_means:                      Vec<f64>,
+    neck_means:                     Vec<f64>,
+    speed_means:                    Vec<f64>,
+    speed_std_deviations:           Vec<f64>,
+    world_colors:                   Vec<u32>,
+    lion_speeds:                    Vec<u32>,
+    tree_heights:                   Vec<u32>,
+    allele_entropies:               Vec<Vec<f64>>,
+    hamming_distances:              Vec<f64>,
+    heterozygosities:               Vec<f64>,
+    marker_heterozygosities:        Vec<f64>,
+    preference_means:               Vec<f64>,
+    unique_genomes:                 Vec<u32>,
+    effective_population_sizes:     Vec<f64>,
+    color_selection_differentials:  Vec<f64>,
+    height_selection_differentials: Vec<f64>,
+    speed_selection_differentials:  Vec<f64>,
+    color_responses:                Vec<f64>,
+    height_responses:               Vec<f64>,
+    speed_responses:                Vec<f64>,
+    fitness_minimums:               Vec<f64>,
+    fitness_lower_quartiles:        Vec<f64>,
+    fitness_medians:                Vec<f64>,
+    fitness_means:                  Vec<f64>,
+    fitness_upper_quartiles:        Vec<f64>,
+    fitness_maximums:               Vec<f64>,
+    best_colors:                    Vec<u32>,
+    best_heights:                   Vec<u32>,
+    best_speeds:                    Vec<u32>,
+    reproductive_success_variances: Vec<f64>,
+    color_histograms:               Vec<Vec<u32>>,
+    height_histograms:              Vec<Vec<u32>>,
+    speed_histograms:               Vec<Vec<u32>>,
+    distributions:                  Vec<Vec<Distribution>>,
+    loci:                           LocusHistory,
+    termination:                    Option<Reason>,
+    generations:                    u32
 }
 
 impl Statistics {
     pub fn new() -> Self {
         Statistics {
-            color_means:           vec![],
-            color_std_deviations:  vec![],
-            height_means:          vec![],
-            height_std_deviations: vec![],
-            leg_means:             vec![],
-            neck_means:            vec![],
-            speed_means:           vec![],
-            speed_std_deviations:  vec![],
-            world_colors:          vec![],
-            lion_speeds:           vec![],
-            tree_heights:          vec![],
-            generations:           0
+            color_means:                    vec![],
+            color_std_deviations:           vec![],
+            height_means:                   vec![],
+            height_std_deviations:          vec![],
+            leg_means:                      vec![],
+            neck_means:                     vec![],
+            speed_means:                    vec![],
+            speed_std_deviations:           vec![],
+            world_colors:                   vec![],
+            lion_speeds:                    vec![],
+            tree_heights:                   vec![],
+            allele_entropies:               vec![],
+            hamming_distances:              vec![],
+            heterozygosities:               vec![],
+            marker_heterozygosities:        vec![],
+            preference_means:               vec![],
+            unique_genomes:                 vec![],
+            effective_population_sizes:     vec![],
+            color_selection_differentials:  vec![],
+            height_selection_differentials: vec![],
+            speed_selection_differentials:  vec![],
+            color_responses:                vec![],
+            height_responses:               vec![],
+            speed_responses:                vec![],
+            fitness_minimums:               vec![],
+            fitness_lower_quartiles:        vec![],
+            fitness_medians:                vec![],
+            fitness_means:                  vec![],
+            fitness_upper_quartiles:        vec![],
+            fitness_maximums:               vec![],
+            best_colors:                    vec![],
+            best_heights:                   vec![],
+            best_speeds:                    vec![],
+            reproductive_success_variances: vec![],
+            color_histograms:               vec![],
+            height_histograms:              vec![],
+            speed_histograms:               vec![],
+            distributions:                  vec![],
+            loci:                           LocusHistory::new(),
+            termination:                    None,
+            generations:                    0
         }
     }
 
@@ -71,6 +161,39 @@ impl Statistics {
         let speed_mean    = statistical::mean(&speeds);
         let speed_stddev  = statistical::standard_deviation(&speeds, None);
 
+        let fitnesses     = world::calculate_fitnesses(&world, &world.tower);
+        let genomes       = world.tower.iter().map(|giraffe| {
+            giraffe.genome()
+        }).collect::<Vec<Vec<u8>>>();
+        let allele_counts = genetics::allele_counts(&genomes);
+        let marker_counts = genetics::allele_counts(&world.tower.iter().map(|giraffe| {
+            giraffe.markers().clone()
+        }).collect());
+
+        let mut sorted_fitnesses = fitnesses.iter().map(|&fitness| {
+            fitness as f64
+        }).collect::<Vec<f64>>();
+
+        sorted_fitnesses.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let best_index = fitnesses.iter().enumerate().fold(0, |best, (i, &fitness)| {
+            if fitness > fitnesses[best] { i } else { best }
+        });
+        let best_giraffe = &world.tower[best_index];
+
+        if !world.parents.is_empty() {
+            self.reproductive_success_variances.push(
+                reproductive_success_variance(&world.parents)
+            );
+        }
+
+        if let (Some(&previous_color), Some(&previous_height), Some(&previous_speed)) =
+            (self.color_means.last(), self.height_means.last(), self.speed_means.last()) {
+            self.color_responses.push(color_mean - previous_color);
+            self.height_responses.push(height_mean - previous_height);
+            self.speed_responses.push(speed_mean - previous_speed);
+        }
+
         self.color_means.push(color_mean);
         self.color_std_deviations.push(color_stddev);
         self.height_means.push(height_mean);
@@ -80,6 +203,48 @@ impl Statistics {
         self.speed_means.push(speed_mean);
         self.speed_std_deviations.push(speed_stddev);
 
+        self.allele_entropies.push(genetics::locus_entropies(&allele_counts));
+        self.loci.update(&allele_counts);
+        self.hamming_distances.push(genetics::mean_pairwise_hamming_distance(&allele_counts));
+        self.heterozygosities.push(genetics::heterozygosity(&allele_counts));
+
+        if !marker_counts.is_empty() {
+            self.marker_heterozygosities.push(genetics::heterozygosity(&marker_counts));
+        }
+
+        if world.tower.iter().any(|giraffe| giraffe.has_preference()) {
+            self.preference_means.push(statistical::mean(&world.tower.iter().map(|giraffe| {
+                giraffe.preference() as f64
+            }).collect::<Vec<f64>>()));
+        }
+        self.unique_genomes.push(genetics::unique_genomes(&genomes) as u32);
+        self.effective_population_sizes.push(genetics::effective_population_size(&fitnesses));
+        self.color_selection_differentials.push(genetics::selection_differential(&colors, &fitnesses));
+        self.height_selection_differentials.push(genetics::selection_differential(&sizes, &fitnesses));
+        self.speed_selection_differentials.push(genetics::selection_differential(&speeds, &fitnesses));
+
+        self.fitness_minimums.push(*sorted_fitnesses.first().unwrap());
+        self.fitness_lower_quartiles.push(quantile(&sorted_fitnesses, 0.25));
+        self.fitness_medians.push(quantile(&sorted_fitnesses, 0.5));
+        self.fitness_means.push(statistical::mean(&sorted_fitnesses));
+        self.fitness_upper_quartiles.push(quantile(&sorted_fitnesses, 0.75));
+        self.fitness_maximums.push(*sorted_fitnesses.last().unwrap());
+        self.best_colors.push(best_giraffe.color());
+        self.best_heights.push(best_giraffe.height());
+        self.best_speeds.push(best_giraffe.speed());
+
+        self.color_histograms.push(histogram::histogram(&colors, histogram::COLOR_BINS));
+        self.height_histograms.push(histogram::histogram(&sizes, histogram::HEIGHT_BINS));
+        self.speed_histograms.push(histogram::histogram(&speeds, histogram::SPEED_BINS));
+
+        self.distributions.push(vec![
+            Distribution::new(&colors),
+            Distribution::new(&sizes),
+            Distribution::new(&speeds),
+            Distribution::new(&leg_lengths),
+            Distribution::new(&neck_lengths)
+        ]);
+
         self.world_colors.push(world.options.color);
         self.lion_speeds.push(world.options.lion_speed);
         self.tree_heights.push(world.options.tree_height);
@@ -88,39 +253,134 @@ impl Statistics {
     }
 
     pub fn print_latest(&self) {
-        let latest_color_mean    = self.color_means.last();
-        let latest_height_mean   = self.height_means.last();
-        let latest_speed_mean    = self.speed_means.last();
-        let latest_world_color   = self.world_colors.last();
-        let latest_lion_speed    = self.lion_speeds.last();
-        let latest_tree_height   = self.tree_heights.last();
-
-        if self.generations != 0 {
-            println!(
-                "g-color: {}, color: {}, g-height: {}, t-height, {}, g-speed: {}, l-speed: {}",
-                latest_color_mean.unwrap(),
-                latest_world_color.unwrap(),
-                latest_height_mean.unwrap(),
-                latest_tree_height.unwrap(),
-                latest_speed_mean.unwrap(),
-                latest_lion_speed.unwrap()
-            );
-        } else {
-            println!("No stats yet");
+        match self.latest() {
+            Some(latest) => println!(
+                "generation: {}, giraffe color: {:.1}, world color: {}, giraffe height: {:.1}, tree height: {}, giraffe speed: {:.1}, lion speed: {}, mean fitness: {:.3}",
+                latest.generation,
+                latest.color_mean,
+                latest.world_color,
+                latest.height_mean,
+                latest.tree_height,
+                latest.speed_mean,
+                latest.lion_speed,
+                latest.fitness_mean
+            ),
+            None => println!("No stats yet")
         }
     }
 
-    pub fn generate_color_figure(&self, destination_file: &str) {
+    pub fn latest(&self) -> Option<Snapshot> {
+        if self.color_means.is_empty() {
+            return None;
+        }
+
+        let last = self.color_means.len() - 1;
+
+        Some(Snapshot {
+            generation:          self.generations,
+            color_mean:          self.color_means[last],
+            world_color:         self.world_colors[last],
+            height_mean:         self.height_means[last],
+            tree_height:         self.tree_heights[last],
+            speed_mean:          self.speed_means[last],
+            lion_speed:          self.lion_speeds[last],
+            fitness_mean:        self.fitness_means[last],
+            fitness_maximum:     self.fitness_maximums[last],
+            allele_entropy_mean: statistical::mean(&self.allele_entropies[last]),
+            environment_changed: last > 0 && (
+                self.world_colors[last] != self.world_colors[last - 1] ||
+                    self.tree_heights[last] != self.tree_heights[last - 1] ||
+                    self.lion_speeds[last] != self.lion_speeds[last - 1]
+            )
+        })
+    }
+
+    // Each trait's mean over the generations next to its environment target.
+    pub fn trait_histories(&self) -> Vec<(&'static str, &Vec<f64>, &Vec<u32>)> {
+        vec![
+            ("Color", &self.color_means, &self.world_colors),
+            ("Height", &self.height_means, &self.tree_heights),
+            ("Speed", &self.speed_means, &self.lion_speeds)
+        ]
+    }
+
+    // Every per-generation series that replicates can be aggregated over,
+    // named as in statistics.csv.
+    pub fn series(&self) -> Vec<(&'static str, Vec<f64>)> {
+        let as_f64 = |values: &Vec<u32>| values.iter().map(|&value| value as f64).collect::<Vec<f64>>();
+
+        vec![
+            ("color_mean", self.color_means.clone()),
+            ("height_mean", self.height_means.clone()),
+            ("leg_mean", self.leg_means.clone()),
+            ("neck_mean", self.neck_means.clone()),
+            ("speed_mean", self.speed_means.clone()),
+            ("world_color", as_f64(&self.world_colors)),
+            ("lion_speed", as_f64(&self.lion_speeds)),
+            ("tree_height", as_f64(&self.tree_heights)),
+            ("mean_pairwise_hamming_distance", self.hamming_distances.clone()),
+            ("unique_genomes", as_f64(&self.unique_genomes)),
+            ("effective_population_size", self.effective_population_sizes.clone()),
+            ("fitness_median", self.fitness_medians.clone()),
+            ("fitness_mean", self.fitness_means.clone()),
+            ("fitness_maximum", self.fitness_maximums.clone())
+        ]
+    }
+
+    pub fn print_summary<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if self.height_std_deviations.is_empty() {
+            writeln!(out, "No stats yet")?;
+            return Ok(());
+        }
+
+        let (min_generation, min_stddev) = self.height_std_deviations.iter()
+            .enumerate()
+            .fold((0, self.height_std_deviations[0]), |(min_i, min), (i, &stddev)| {
+                if stddev < min { (i, stddev) } else { (min_i, min) }
+            });
+
+        writeln!(
+            out,
+            "height std deviation: initial: {}, minimum: {} (generation {}), final: {}",
+            self.height_std_deviations.first().unwrap(),
+            min_stddev,
+            min_generation,
+            self.height_std_deviations.last().unwrap()
+        )?;
+
+        if let Some(distributions) = self.distributions.last() {
+            let bimodal = DISTRIBUTION_TRAITS.iter().zip(distributions).filter(|&(_, distribution)| {
+                distribution.is_bimodal()
+            }).map(|(&name, distribution)| {
+                format!("{} ({:.3})", name, distribution.bimodality)
+            }).collect::<Vec<String>>();
+
+            if !bimodal.is_empty() {
+                writeln!(out, "bimodal traits: {}", bimodal.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_color_figure(&self, terminal: &str, destination_file: &str, replicates: Option<&Replicates>) {
         let x: Vec<f64> = (0..self.generations).into_iter().map(|i| {
             i as f64
         }).collect();
 
         let mut figure = gnuplot::Figure::new();
 
-        figure.set_terminal("png", destination_file);
-        figure.axes2d()
+        figure.set_terminal(terminal, destination_file);
+
+        let axes = plot_replicates(figure.axes2d(), replicates, "color_mean", "Color")
             .set_x_label("Generation", &vec![])
             .set_y_label("Color", &vec![])
+            .fill_between(
+                &x,
+                &lower_band(&self.color_means, &self.color_std_deviations),
+                &upper_band(&self.color_means, &self.color_std_deviations),
+                &[gnuplot::Caption("Color ±1σ"), gnuplot::Color("gray"), gnuplot::FillAlpha(0.3)]
+            )
             .lines(
                 &x,
                 &self.color_means,
@@ -132,20 +392,35 @@ impl Statistics {
                 &[gnuplot::Caption("Tree Color"), gnuplot::Color("red")]
             );
 
+        if !self.preference_means.is_empty() {
+            axes.lines(
+                &x,
+                &self.preference_means,
+                &[gnuplot::Caption("Mean Preferred Color"), gnuplot::Color("blue"), gnuplot::LineStyle(gnuplot::Dash)]
+            );
+        }
+
         figure.show();
     }
 
-    pub fn generate_height_figure(&self, destination_file: &str) {
+    pub fn generate_height_figure(&self, terminal: &str, destination_file: &str, replicates: Option<&Replicates>) {
         let x: Vec<f64> = (0..self.generations).into_iter().map(|i| {
             i as f64
         }).collect();
 
         let mut figure = gnuplot::Figure::new();
 
-        figure.set_terminal("png", destination_file);
-        figure.axes2d()
+        figure.set_terminal(terminal, destination_file);
+
+        plot_replicates(figure.axes2d(), replicates, "height_mean", "Height")
             .set_x_label("Generation", &vec![])
             .set_y_label("Height", &vec![])
+            .fill_between(
+                &x,
+                &lower_band(&self.height_means, &self.height_std_deviations),
+                &upper_band(&self.height_means, &self.height_std_deviations),
+                &[gnuplot::Caption("Height ±1σ"), gnuplot::Color("gray"), gnuplot::FillAlpha(0.3)]
+            )
             .lines(
                 &x,
                 &self.height_means,
@@ -170,17 +445,24 @@ impl Statistics {
         figure.show();
     }
 
-    pub fn generate_speed_figure(&self, destination_file: &str) {
+    pub fn generate_speed_figure(&self, terminal: &str, destination_file: &str, replicates: Option<&Replicates>) {
         let x: Vec<f64> = (0..self.generations).into_iter().map(|i| {
             i as f64
         }).collect();
 
         let mut figure = gnuplot::Figure::new();
 
-        figure.set_terminal("png", destination_file);
-        figure.axes2d()
+        figure.set_terminal(terminal, destination_file);
+
+        plot_replicates(figure.axes2d(), replicates, "speed_mean", "Speed")
             .set_x_label("Generation", &vec![])
             .set_y_label("Speed", &vec![])
+            .fill_between(
+                &x,
+                &lower_band(&self.speed_means, &self.speed_std_deviations),
+                &upper_band(&self.speed_means, &self.speed_std_deviations),
+                &[gnuplot::Caption("Speed ±1σ"), gnuplot::Color("gray"), gnuplot::FillAlpha(0.3)]
+            )
             .lines(
                 &x,
                 &self.speed_means,
@@ -194,4 +476,536 @@ impl Statistics {
 
         figure.show();
     }
+
+    pub fn generate_diversity_figure(&self, terminal: &str, destination_file: &str) {
+        let x: Vec<f64> = (0..self.generations).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let mean_entropies = self.allele_entropies.iter().map(|entropies| {
+            statistical::mean(&entropies)
+        }).collect::<Vec<f64>>();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+        figure.axes2d()
+            .set_pos_grid(3, 1, 0)
+            .set_x_label("Generation", &vec![])
+            .set_y_label("Genetic Diversity", &vec![])
+            .lines(
+                &x,
+                &mean_entropies,
+                &[gnuplot::Caption("Mean Allele Entropy (bits)"), gnuplot::Color("black")]
+            )
+            .lines(
+                &x,
+                &self.hamming_distances,
+                &[gnuplot::Caption("Mean Pairwise Hamming Distance"), gnuplot::Color("blue")]
+            );
+        figure.axes2d()
+            .set_pos_grid(3, 1, 1)
+            .set_x_label("Generation", &vec![])
+            .set_y_label("Giraffes", &vec![])
+            .lines(
+                &x,
+                &self.unique_genomes,
+                &[gnuplot::Caption("Unique Genomes"), gnuplot::Color("black")]
+            )
+            .lines(
+                &x,
+                &self.effective_population_sizes,
+                &[gnuplot::Caption("Effective Population Size"), gnuplot::Color("blue")]
+            );
+        figure.axes2d()
+            .set_pos_grid(3, 1, 2)
+            .set_x_label("Generation", &vec![])
+            .set_y_label("Height", &vec![])
+            .lines(
+                &x,
+                &self.height_selection_differentials,
+                &[gnuplot::Caption("Selection Differential"), gnuplot::Color("black")]
+            )
+            .lines(
+                &x,
+                &self.height_responses,
+                &[gnuplot::Caption("Response to Selection"), gnuplot::Color("blue")]
+            );
+
+        figure.show();
+    }
+
+    pub fn generate_fitness_figure(&self, terminal: &str, destination_file: &str) {
+        let x: Vec<f64> = (0..self.generations).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+        figure.axes2d()
+            .set_x_label("Generation", &vec![])
+            .set_y_label("Fitness", &vec![])
+            .lines(
+                &x,
+                &self.fitness_maximums,
+                &[gnuplot::Caption("Maximum Fitness"), gnuplot::Color("green")]
+            )
+            .lines(
+                &x,
+                &self.fitness_upper_quartiles,
+                &[gnuplot::Caption("Upper Quartile"), gnuplot::Color("blue"), gnuplot::LineStyle(gnuplot::Dash)]
+            )
+            .lines(
+                &x,
+                &self.fitness_medians,
+                &[gnuplot::Caption("Median Fitness"), gnuplot::Color("blue")]
+            )
+            .lines(
+                &x,
+                &self.fitness_lower_quartiles,
+                &[gnuplot::Caption("Lower Quartile"), gnuplot::Color("blue"), gnuplot::LineStyle(gnuplot::Dash)]
+            )
+            .lines(
+                &x,
+                &self.fitness_minimums,
+                &[gnuplot::Caption("Minimum Fitness"), gnuplot::Color("red")]
+            )
+            .lines(
+                &x,
+                &self.fitness_means,
+                &[gnuplot::Caption("Mean Fitness"), gnuplot::Color("black")]
+            );
+
+        figure.show();
+    }
+
+    pub fn heterozygosities(&self) -> &Vec<f64> {
+        &self.heterozygosities
+    }
+
+    // Empty unless the giraffes carry neutral markers.
+    pub fn marker_heterozygosities(&self) -> &Vec<f64> {
+        &self.marker_heterozygosities
+    }
+
+    pub fn loci(&self) -> &LocusHistory {
+        &self.loci
+    }
+
+    // Why the run ended, once it has.
+    pub fn termination(&self) -> Option<Reason> {
+        self.termination
+    }
+
+    pub fn set_termination(&mut self, reason: Reason) {
+        self.termination = Some(reason);
+    }
+
+    // Generations where the tree height, lion speed or environment color changed.
+    pub fn environment_changes(&self) -> Vec<u32> {
+        (1..self.tree_heights.len()).filter(|&i| {
+            self.tree_heights[i] != self.tree_heights[i - 1] ||
+                self.lion_speeds[i] != self.lion_speeds[i - 1] ||
+                self.world_colors[i] != self.world_colors[i - 1]
+        }).map(|i| i as u32).collect::<Vec<u32>>()
+    }
+
+    pub fn generate_dashboard_figure(&self, terminal: &str, destination_file: &str) {
+        let x: Vec<f64> = (0..self.generations).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let mean_entropies = self.allele_entropies.iter().map(|entropies| {
+            statistical::mean(&entropies)
+        }).collect::<Vec<f64>>();
+
+        let changes = self.environment_changes();
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+
+        mark_environment_changes(figure.axes2d(), &changes)
+            .set_pos_grid(5, 1, 0)
+            .set_y_label("Color", &vec![])
+            .fill_between(
+                &x,
+                &lower_band(&self.color_means, &self.color_std_deviations),
+                &upper_band(&self.color_means, &self.color_std_deviations),
+                &[gnuplot::Color("gray"), gnuplot::FillAlpha(0.3)]
+            )
+            .lines(&x, &self.color_means, &[gnuplot::Caption("Mean Color"), gnuplot::Color("black")])
+            .lines(&x, &self.world_colors, &[gnuplot::Caption("Tree Color"), gnuplot::Color("red")]);
+
+        mark_environment_changes(figure.axes2d(), &changes)
+            .set_pos_grid(5, 1, 1)
+            .set_y_label("Height", &vec![])
+            .fill_between(
+                &x,
+                &lower_band(&self.height_means, &self.height_std_deviations),
+                &upper_band(&self.height_means, &self.height_std_deviations),
+                &[gnuplot::Color("gray"), gnuplot::FillAlpha(0.3)]
+            )
+            .lines(&x, &self.height_means, &[gnuplot::Caption("Mean Height"), gnuplot::Color("black")])
+            .lines(&x, &self.tree_heights, &[gnuplot::Caption("Tree Height"), gnuplot::Color("red")]);
+
+        mark_environment_changes(figure.axes2d(), &changes)
+            .set_pos_grid(5, 1, 2)
+            .set_y_label("Speed", &vec![])
+            .fill_between(
+                &x,
+                &lower_band(&self.speed_means, &self.speed_std_deviations),
+                &upper_band(&self.speed_means, &self.speed_std_deviations),
+                &[gnuplot::Color("gray"), gnuplot::FillAlpha(0.3)]
+            )
+            .lines(&x, &self.speed_means, &[gnuplot::Caption("Mean Speed"), gnuplot::Color("black")])
+            .lines(&x, &self.lion_speeds, &[gnuplot::Caption("Lion Speed"), gnuplot::Color("red")]);
+
+        mark_environment_changes(figure.axes2d(), &changes)
+            .set_pos_grid(5, 1, 3)
+            .set_y_label("Fitness", &vec![])
+            .fill_between(
+                &x,
+                &self.fitness_lower_quartiles,
+                &self.fitness_upper_quartiles,
+                &[gnuplot::Color("blue"), gnuplot::FillAlpha(0.2)]
+            )
+            .lines(&x, &self.fitness_means, &[gnuplot::Caption("Mean Fitness"), gnuplot::Color("black")])
+            .lines(&x, &self.fitness_maximums, &[gnuplot::Caption("Maximum Fitness"), gnuplot::Color("green")]);
+
+        mark_environment_changes(figure.axes2d(), &changes)
+            .set_pos_grid(5, 1, 4)
+            .set_x_label("Generation", &vec![])
+            .set_y_label("Diversity", &vec![])
+            .lines(&x, &mean_entropies, &[gnuplot::Caption("Mean Allele Entropy"), gnuplot::Color("black")])
+            .lines(&x, &self.hamming_distances, &[gnuplot::Caption("Mean Hamming Distance"), gnuplot::Color("blue")]);
+
+        figure.show();
+    }
+
+    pub fn generate_color_histogram_figure(&self, terminal: &str, destination_file: &str) {
+        generate_histogram_figure(
+            terminal,
+            destination_file,
+            "Color",
+            &self.color_histograms,
+            histogram::COLOR_BINS,
+            &self.world_colors
+        );
+    }
+
+    pub fn generate_height_histogram_figure(&self, terminal: &str, destination_file: &str) {
+        generate_histogram_figure(
+            terminal,
+            destination_file,
+            "Height",
+            &self.height_histograms,
+            histogram::HEIGHT_BINS,
+            &self.tree_heights
+        );
+    }
+
+    pub fn generate_speed_histogram_figure(&self, terminal: &str, destination_file: &str) {
+        generate_histogram_figure(
+            terminal,
+            destination_file,
+            "Speed",
+            &self.speed_histograms,
+            histogram::SPEED_BINS,
+            &self.lion_speeds
+        );
+    }
+
+    // Quartile boxes with whiskers out to the extremes every interval
+    // generations for each trait, then every trait's bimodality coefficient.
+    pub fn generate_distribution_figure(&self, terminal: &str, destination_file: &str, interval: u32) {
+        let sampled = (0..self.distributions.len()).filter(|&i| {
+            i as u32 % interval == 0
+        }).collect::<Vec<usize>>();
+        let x       = sampled.iter().map(|&i| i as f64).collect::<Vec<f64>>();
+
+        let all_x: Vec<f64> = (0..self.distributions.len()).into_iter().map(|i| {
+            i as f64
+        }).collect();
+
+        let panels = DISTRIBUTION_TRAITS.len() as u32 + 1;
+
+        let mut figure = gnuplot::Figure::new();
+
+        figure.set_terminal(terminal, destination_file);
+
+        for (panel, name) in DISTRIBUTION_TRAITS.iter().enumerate() {
+            let sample = |statistic: fn(&Distribution) -> f64| -> Vec<f64> {
+                sampled.iter().map(|&i| statistic(&self.distributions[i][panel])).collect()
+            };
+
+            let axes = figure.axes2d()
+                .set_pos_grid(panels, 1, panel as u32)
+                .set_y_label(name, &vec![])
+                .box_and_whisker(
+                    &x,
+                    &sample(|distribution| distribution.lower_quartile),
+                    &sample(|distribution| distribution.minimum),
+                    &sample(|distribution| distribution.maximum),
+                    &sample(|distribution| distribution.upper_quartile),
+                    &[gnuplot::Color("blue"), gnuplot::WhiskerBars(0.5)]
+                )
+                .points(
+                    &x,
+                    &sample(|distribution| distribution.median),
+                    &[gnuplot::Caption("Median"), gnuplot::PointSymbol('_'), gnuplot::Color("black")]
+                );
+
+            // Legs and necks have no target of their own.
+            let environment = match *name {
+                "color"  => Some(&self.world_colors),
+                "height" => Some(&self.tree_heights),
+                "speed"  => Some(&self.lion_speeds),
+                _        => None
+            };
+
+            if let Some(environment) = environment {
+                axes.lines(&all_x, environment, &[gnuplot::Caption("Environment"), gnuplot::Color("red")]);
+            }
+        }
+
+        let axes = figure.axes2d()
+            .set_pos_grid(panels, 1, panels - 1)
+            .set_x_label("Generation", &vec![])
+            .set_y_label("bimodality", &vec![])
+            .lines(
+                &all_x,
+                &vec![distribution::BIMODALITY_THRESHOLD; all_x.len()],
+                &[gnuplot::Caption("Threshold"), gnuplot::Color("gray"), gnuplot::LineStyle(gnuplot::Dash)]
+            );
+
+        for (i, name) in DISTRIBUTION_TRAITS.iter().enumerate() {
+            let bimodality = self.distributions.iter().map(|distributions| {
+                distributions[i].bimodality
+            }).collect::<Vec<f64>>();
+
+            axes.lines(&all_x, &bimodality, &[gnuplot::Caption(name), gnuplot::Color(TRAIT_COLORS[i])]);
+        }
+
+        figure.show();
+    }
+
+    pub fn export_csv(&self, destination_file: &str) -> io::Result<()> {
+        let mut file = File::create(destination_file)?;
+
+        let num_loci = self.allele_entropies.first().map_or(0, |entropies| entropies.len());
+
+        let mut header = vec![
+            "generation", "color_mean", "color_std_deviation", "height_mean",
+            "height_std_deviation", "leg_mean", "neck_mean", "speed_mean",
+            "speed_std_deviation", "world_color", "lion_speed", "tree_height",
+            "mean_pairwise_hamming_distance", "unique_genomes", "effective_population_size",
+            "color_selection_differential", "height_selection_differential",
+            "speed_selection_differential", "color_response", "height_response",
+            "speed_response", "fitness_minimum", "fitness_lower_quartile", "fitness_median",
+            "fitness_mean", "fitness_upper_quartile", "fitness_maximum", "best_color",
+            "best_height", "best_speed", "reproductive_success_variance", "heterozygosity",
+            "marker_heterozygosity", "preference_mean"
+        ].into_iter().map(|column| column.to_string()).collect::<Vec<String>>();
+
+        for name in DISTRIBUTION_TRAITS.iter() {
+            for statistic in ["median", "lower_quartile", "upper_quartile", "skewness", "kurtosis", "bimodality"].iter() {
+                header.push(format!("{}_{}", name, statistic));
+            }
+        }
+
+        for locus in 0..num_loci {
+            header.push(format!("locus_{}_entropy", locus));
+        }
+
+        header.push("termination".to_string());
+
+        writeln!(file, "{}", header.join(","))?;
+
+        for i in 0..self.color_means.len() {
+            let mut row = vec![
+                i.to_string(),
+                self.color_means[i].to_string(),
+                self.color_std_deviations[i].to_string(),
+                self.height_means[i].to_string(),
+                self.height_std_deviations[i].to_string(),
+                self.leg_means[i].to_string(),
+                self.neck_means[i].to_string(),
+                self.speed_means[i].to_string(),
+                self.speed_std_deviations[i].to_string(),
+                self.world_colors[i].to_string(),
+                self.lion_speeds[i].to_string(),
+                self.tree_heights[i].to_string(),
+                self.hamming_distances[i].to_string(),
+                self.unique_genomes[i].to_string(),
+                self.effective_population_sizes[i].to_string(),
+                self.color_selection_differentials[i].to_string(),
+                self.height_selection_differentials[i].to_string(),
+                self.speed_selection_differentials[i].to_string(),
+                optional_to_string(self.color_responses.get(i)),
+                optional_to_string(self.height_responses.get(i)),
+                optional_to_string(self.speed_responses.get(i)),
+                self.fitness_minimums[i].to_string(),
+                self.fitness_lower_quartiles[i].to_string(),
+                self.fitness_medians[i].to_string(),
+                self.fitness_means[i].to_string(),
+                self.fitness_upper_quartiles[i].to_string(),
+                self.fitness_maximums[i].to_string(),
+                self.best_colors[i].to_string(),
+                self.best_heights[i].to_string(),
+                self.best_speeds[i].to_string(),
+                optional_to_string(self.reproductive_success_variances.get(i)),
+                self.heterozygosities[i].to_string(),
+                optional_to_string(self.marker_heterozygosities.get(i)),
+                optional_to_string(self.preference_means.get(i))
+            ];
+
+            for distribution in self.distributions[i].iter() {
+                row.push(distribution.median.to_string());
+                row.push(distribution.lower_quartile.to_string());
+                row.push(distribution.upper_quartile.to_string());
+                row.push(distribution.skewness.to_string());
+                row.push(distribution.kurtosis.to_string());
+                row.push(distribution.bimodality.to_string());
+            }
+
+            for entropy in self.allele_entropies[i].iter() {
+                row.push(entropy.to_string());
+            }
+
+            // Only the generation the run ended on carries the reason.
+            row.push(match self.termination {
+                Some(reason) if i == self.color_means.len() - 1 => reason.name().to_string(),
+                _                                                => String::new()
+            });
+
+            writeln!(file, "{}", row.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn optional_to_string(value: Option<&f64>) -> String {
+    value.map_or(String::new(), |value| value.to_string())
+}
+
+fn lower_band(means: &Vec<f64>, std_deviations: &Vec<f64>) -> Vec<f64> {
+    means.iter().zip(std_deviations.iter()).map(|(mean, std_deviation)| {
+        mean - std_deviation
+    }).collect::<Vec<f64>>()
+}
+
+fn upper_band(means: &Vec<f64>, std_deviations: &Vec<f64>) -> Vec<f64> {
+    means.iter().zip(std_deviations.iter()).map(|(mean, std_deviation)| {
+        mean + std_deviation
+    }).collect::<Vec<f64>>()
+}
+
+// The 5-95% band, median and mean of a series across replicate runs, drawn
+// under this run's own lines.
+fn plot_replicates<'l>(
+    axes:       &'l mut gnuplot::Axes2D,
+    replicates: Option<&Replicates>,
+    series:     &str,
+    label:      &str
+) -> &'l mut gnuplot::Axes2D {
+    let (count, band) = match replicates.and_then(|replicates| replicates.band(series)) {
+        Some(band) => (replicates.unwrap().count, band),
+        None       => return axes
+    };
+
+    let x = (0..band.mean.len()).map(|i| i as f64).collect::<Vec<f64>>();
+
+    axes
+        .fill_between(
+            &x,
+            &band.lower,
+            &band.upper,
+            &[gnuplot::Caption(&format!("{} 5-95% of {} runs", label, count)), gnuplot::Color("blue"), gnuplot::FillAlpha(0.15)]
+        )
+        .lines(
+            &x,
+            &band.median,
+            &[gnuplot::Caption(&format!("Median {} of runs", label)), gnuplot::Color("blue"), gnuplot::LineStyle(gnuplot::Dash)]
+        )
+        .lines(
+            &x,
+            &band.mean,
+            &[gnuplot::Caption(&format!("Mean {} of runs", label)), gnuplot::Color("blue")]
+        )
+}
+
+// A dashed vertical line across the panel at each environment change.
+pub fn mark_environment_changes<'l>(axes: &'l mut gnuplot::Axes2D, changes: &Vec<u32>) -> &'l mut gnuplot::Axes2D {
+    for &generation in changes {
+        axes.arrow(
+            gnuplot::Axis(generation as f64),
+            gnuplot::Graph(0.0),
+            gnuplot::Axis(generation as f64),
+            gnuplot::Graph(1.0),
+            &[gnuplot::ArrowType(gnuplot::NoArrow), gnuplot::Color("gray"), gnuplot::LineStyle(gnuplot::Dash)]
+        );
+    }
+
+    axes
+}
+
+fn generate_histogram_figure(
+    terminal:         &str,
+    destination_file: &str,
+    label:            &str,
+    histograms:       &Vec<Vec<u32>>,
+    bins:             histogram::Bins,
+    targets:          &Vec<u32>
+) {
+    let num_generations = histograms.len();
+
+    let x: Vec<f64> = (0..num_generations).into_iter().map(|i| {
+        i as f64
+    }).collect();
+
+    let mut counts = vec![];
+
+    for bin in 0..bins.count {
+        for generation in 0..num_generations {
+            counts.push(histograms[generation][bin]);
+        }
+    }
+
+    let mut figure = gnuplot::Figure::new();
+
+    figure.set_terminal(terminal, destination_file);
+    figure.axes2d()
+        .set_x_label("Generation", &vec![])
+        .set_y_label(label, &vec![])
+        .set_cb_label("Giraffes", &vec![])
+        .image(
+            &counts,
+            bins.count,
+            num_generations,
+            Some((0.0, 0.0, num_generations as f64, (bins.size as usize * bins.count) as f64)),
+            &[]
+        )
+        .lines(
+            &x,
+            targets,
+            &[gnuplot::Caption("Environment"), gnuplot::Color("red")]
+        );
+
+    figure.show();
+}
+
+// Offspring counts are attributed to the previous generation, with a parent
+// credited once for every child slot it filled.
+fn reproductive_success_variance(parents: &Vec<(usize, usize)>) -> f64 {
+    let mut offspring_counts = vec![0.0; parents.len()];
+
+    for &(parent1, parent2) in parents.iter() {
+        offspring_counts[parent1] += 1.0;
+        offspring_counts[parent2] += 1.0;
+    }
+
+    statistical::population_variance(&offspring_counts, None)
 }
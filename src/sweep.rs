@@ -0,0 +1,396 @@
+use std::fs::File;
+use std::io;
+use std::io::Write;
+
+use config;
+use options::{Options, OptionsError};
+use simulation;
+use statistics::Statistics;
+use termination::{Reason, Rules};
+
+
+// Final generation metrics compared across the sweep.
+pub const METRICS: [&'static str; 5] = ["fitness_mean", "fitness_maximum", "color_gap", "height_gap", "speed_gap"];
+
+// Two sided 95% t values for 1 to 30 degrees of freedom.
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042
+];
+const Z_95: f64 = 1.960;
+
+pub struct Parameter {
+    pub key:    &'static str,
+    pub values: Vec<String>
+}
+
+pub struct Combination {
+    pub settings: Vec<(&'static str, String)>,
+    pub options:  Options
+}
+
+impl Combination {
+    pub fn describe(&self) -> String {
+        self.settings.iter().map(|&(key, ref value)| {
+            format!("{}={}", key, value)
+        }).collect::<Vec<String>>().join(", ")
+    }
+}
+
+pub struct Outcome {
+    pub combination: usize,
+    pub replicate:   u32,
+    pub seed:        u64,
+    pub termination: Reason,
+    pub generations: u32,
+    pub metrics:     Vec<f64>
+}
+
+// A parameter is a setting with either a list of values, as in
+// mutrate=0.1,1,5, or a numeric range with a step, as in size=100:1000:300.
+pub fn parse_parameter(parameter: &str) -> Result<Parameter, OptionsError> {
+    let malformed = || OptionsError::MalformedValue {
+        option:   "sweep parameter".to_string(),
+        value:    parameter.to_string(),
+        expected: "name=a,b,c or name=start:end:step"
+    };
+
+    let parts = parameter.splitn(2, '=').map(|part| part.trim()).collect::<Vec<&str>>();
+
+    if parts.len() != 2 || parts[1].is_empty() {
+        return Err(malformed());
+    }
+
+    let key = config::SETTINGS.iter().find(|&&setting| setting == parts[0]).ok_or_else(|| {
+        OptionsError::UnknownValue {
+            option:   "sweep parameter".to_string(),
+            value:    parts[0].to_string(),
+            expected: config::SETTING_NAMES
+        }
+    })?;
+
+    let values = if parts[1].contains(':') {
+        let bounds = parts[1].split(':').map(|bound| bound.trim().parse::<f64>().ok()).collect::<Vec<Option<f64>>>();
+
+        match bounds.as_slice() {
+            &[Some(start), Some(end), Some(step)] if step > 0.0 && start <= end => {
+                let count = ((end - start) / step + 1e-9).floor() as usize + 1;
+
+                (0..count).map(|i| (start + i as f64 * step).to_string()).collect::<Vec<String>>()
+            },
+            _ => return Err(malformed())
+        }
+    } else {
+        parts[1].split(',').map(|value| value.trim().to_string()).filter(|value| !value.is_empty()).collect()
+    };
+
+    if values.is_empty() {
+        return Err(malformed());
+    }
+
+    Ok(Parameter { key: key, values: values })
+}
+
+// Every combination of the parameters' values, the last parameter varying
+// fastest.
+pub fn combinations(parameters: &Vec<Parameter>) -> Vec<Vec<(&'static str, String)>> {
+    parameters.iter().fold(vec![vec![]], |combinations, parameter| {
+        combinations.iter().flat_map(|combination| {
+            parameter.values.iter().map(move |value| {
+                let mut settings = combination.clone();
+
+                settings.push((parameter.key, value.clone()));
+                settings
+            })
+        }).collect()
+    })
+}
+
+fn final_metrics(statistics: &Statistics) -> Vec<f64> {
+    let latest = statistics.latest().expect("Simulation recorded no generations");
+
+    vec![
+        latest.fitness_mean,
+        latest.fitness_maximum,
+        (latest.color_mean - latest.world_color as f64).abs(),
+        (latest.height_mean - latest.tree_height as f64).abs(),
+        (latest.speed_mean - latest.lion_speed as f64).abs()
+    ]
+}
+
+// Runs every combination once per replicate across the worker threads, each
+// run stopping early under the same rules as a single simulation. Replicate r
+// of every combination is seeded with seed + r, so combinations are compared
+// on the same random streams.
+pub fn run<W: Write>(
+    combinations: &Vec<Combination>,
+    replicates:   u32,
+    seed:         u64,
+    threads:      usize,
+    rules:        Rules,
+    notes:        &mut W
+) -> Vec<Outcome> {
+    let mut runs = vec![];
+    let mut jobs = vec![];
+
+    for (index, combination) in combinations.iter().enumerate() {
+        for replicate in 0..replicates {
+            let seed = seed.wrapping_add(replicate as u64);
+
+            runs.push((index, replicate, seed));
+            jobs.push(simulation::Job { options: combination.options.clone(), rules: rules, seed: seed });
+        }
+    }
+
+    let total = jobs.len();
+
+    let results = simulation::simulate_all(jobs, threads, |statistics| {
+        let reason      = statistics.termination().unwrap_or(Reason::Completed);
+        let generations = statistics.latest().map_or(0, |latest| latest.generation);
+
+        (reason, generations, final_metrics(&statistics))
+    }, |finished, &(reason, generations, ref metrics)| {
+        writeln!(
+            notes,
+            "Finished run {}/{}, mean fitness {:.3}, {} at generation {}",
+            finished,
+            total,
+            metrics[0],
+            reason.name(),
+            generations
+        ).expect("Failed to report progress");
+    });
+
+    runs.into_iter().zip(results).map(|((combination, replicate, seed), (reason, generations, metrics))| {
+        Outcome {
+            combination: combination,
+            replicate:   replicate,
+            seed:        seed,
+            termination: reason,
+            generations: generations,
+            metrics:     metrics
+        }
+    }).collect()
+}
+
+// The mean and the half width of its 95% confidence interval.
+fn mean_and_interval(values: &Vec<f64>) -> (f64, f64) {
+    let n    = values.len();
+    let mean = values.iter().fold(0.0, |acc, value| acc + value) / n as f64;
+
+    if n < 2 {
+        return (mean, 0.0);
+    }
+
+    let variance = values.iter().fold(0.0, |acc, value| acc + (value - mean).powi(2)) / (n - 1) as f64;
+    let t        = if n - 1 <= T_95.len() { T_95[n - 2] } else { Z_95 };
+
+    (mean, t * (variance / n as f64).sqrt())
+}
+
+fn summarize(combinations: &Vec<Combination>, outcomes: &Vec<Outcome>) -> Vec<Vec<(f64, f64)>> {
+    (0..combinations.len()).map(|index| {
+        (0..METRICS.len()).map(|metric| {
+            let values = outcomes.iter()
+                .filter(|outcome| outcome.combination == index)
+                .map(|outcome| outcome.metrics[metric])
+                .collect::<Vec<f64>>();
+
+            mean_and_interval(&values)
+        }).collect()
+    }).collect()
+}
+
+pub fn print_table<W: Write>(
+    out:          &mut W,
+    combinations: &Vec<Combination>,
+    outcomes:     &Vec<Outcome>
+) -> io::Result<()> {
+    let keys = combinations[0].settings.iter().map(|&(key, _)| key).collect::<Vec<&str>>();
+
+    let mut header = keys.iter().map(|key| format!("{:>12}", key)).collect::<Vec<String>>();
+
+    header.extend(METRICS.iter().map(|metric| format!("{:>20}", metric)));
+
+    writeln!(out, "{}", header.join(" "))?;
+
+    for (combination, summary) in combinations.iter().zip(summarize(&combinations, &outcomes)) {
+        let mut row = combination.settings.iter().map(|&(_, ref value)| {
+            format!("{:>12}", value)
+        }).collect::<Vec<String>>();
+
+        row.extend(summary.iter().map(|&(mean, interval)| {
+            format!("{:>20}", format!("{:.3} ± {:.3}", mean, interval))
+        }));
+
+        writeln!(out, "{}", row.join(" "))?;
+    }
+
+    Ok(())
+}
+
+pub fn write_summary_csv(
+    destination:  &str,
+    combinations: &Vec<Combination>,
+    outcomes:     &Vec<Outcome>,
+    replicates:   u32
+) -> io::Result<()> {
+    let mut file = File::create(destination)?;
+
+    let mut header = combinations[0].settings.iter().map(|&(key, _)| key.to_string()).collect::<Vec<String>>();
+
+    header.push("replicates".to_string());
+
+    for metric in METRICS.iter() {
+        header.push(format!("{}_mean", metric));
+        header.push(format!("{}_ci_low", metric));
+        header.push(format!("{}_ci_high", metric));
+    }
+
+    writeln!(file, "{}", header.join(","))?;
+
+    for (combination, summary) in combinations.iter().zip(summarize(&combinations, &outcomes)) {
+        let mut row = combination.settings.iter().map(|&(_, ref value)| value.clone()).collect::<Vec<String>>();
+
+        row.push(replicates.to_string());
+
+        for (mean, interval) in summary {
+            row.push(mean.to_string());
+            row.push((mean - interval).to_string());
+            row.push((mean + interval).to_string());
+        }
+
+        writeln!(file, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
+
+pub fn write_runs_csv(destination: &str, combinations: &Vec<Combination>, outcomes: &Vec<Outcome>) -> io::Result<()> {
+    let mut file = File::create(destination)?;
+
+    let mut header = combinations[0].settings.iter().map(|&(key, _)| key.to_string()).collect::<Vec<String>>();
+
+    header.push("replicate".to_string());
+    header.push("seed".to_string());
+    header.push("termination".to_string());
+    header.push("generations".to_string());
+    header.extend(METRICS.iter().map(|metric| metric.to_string()));
+
+    writeln!(file, "{}", header.join(","))?;
+
+    for outcome in outcomes {
+        let mut row = combinations[outcome.combination].settings.iter().map(|&(_, ref value)| {
+            value.clone()
+        }).collect::<Vec<String>>();
+
+        row.push((outcome.replicate + 1).to_string());
+        row.push(outcome.seed.to_string());
+        row.push(outcome.termination.name().to_string());
+        row.push(outcome.generations.to_string());
+        row.extend(outcome.metrics.iter().map(|metric| metric.to_string()));
+
+        writeln!(file, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(parameter: &str) -> Vec<String> {
+        parse_parameter(parameter).unwrap_or_else(|error| panic!("{}", error)).values
+    }
+
+    fn is_malformed(parameter: &str) -> bool {
+        match parse_parameter(parameter) {
+            Err(OptionsError::MalformedValue { .. }) => true,
+            _                                        => false
+        }
+    }
+
+    fn standard_error(values: &Vec<f64>) -> f64 {
+        let n        = values.len() as f64;
+        let mean     = values.iter().fold(0.0, |acc, value| acc + value) / n;
+        let variance = values.iter().fold(0.0, |acc, value| acc + (value - mean).powi(2)) / (n - 1.0);
+
+        (variance / n).sqrt()
+    }
+
+    #[test]
+    fn lists_give_their_values() {
+        assert_eq!(values("mutrate=0.1, 1,5"), vec!["0.1", "1", "5"]);
+    }
+
+    #[test]
+    fn ranges_include_both_ends() {
+        assert_eq!(values("size=100:1000:300"), vec!["100", "400", "700", "1000"]);
+        assert_eq!(values("size=100:900:300"), vec!["100", "400", "700"]);
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        match parse_parameter("spots=1,2") {
+            Err(OptionsError::UnknownValue { .. }) => {},
+            _                                      => panic!("expected an unknown value")
+        }
+    }
+
+    #[test]
+    fn malformed_parameters_are_rejected() {
+        assert!(is_malformed("size"));
+        assert!(is_malformed("size="));
+        assert!(is_malformed("size=,"));
+        assert!(is_malformed("size=10:1:1"));
+        assert!(is_malformed("size=1:10:0"));
+        assert!(is_malformed("size=1:10"));
+    }
+
+    #[test]
+    fn combinations_vary_the_last_parameter_fastest() {
+        let parameters = vec![
+            Parameter { key: "size", values: vec!["10".to_string(), "20".to_string()] },
+            Parameter { key: "color", values: vec!["1".to_string(), "2".to_string(), "3".to_string()] }
+        ];
+
+        let settings = combinations(&parameters).iter().map(|combination| {
+            combination.iter().map(|&(key, ref value)| format!("{}={}", key, value)).collect::<Vec<String>>().join(",")
+        }).collect::<Vec<String>>();
+
+        assert_eq!(settings, vec![
+            "size=10,color=1", "size=10,color=2", "size=10,color=3",
+            "size=20,color=1", "size=20,color=2", "size=20,color=3"
+        ]);
+    }
+
+    #[test]
+    fn no_parameters_make_one_empty_combination() {
+        assert_eq!(combinations(&vec![]), vec![vec![]]);
+    }
+
+    #[test]
+    fn a_single_value_has_no_interval() {
+        assert_eq!(mean_and_interval(&vec![3.0]), (3.0, 0.0));
+    }
+
+    #[test]
+    fn intervals_use_t_for_small_samples_and_z_beyond() {
+        let pair = vec![1.0, 3.0];
+
+        assert_eq!(mean_and_interval(&pair), (2.0, T_95[0] * standard_error(&pair)));
+
+        let thirty_one = (0..31).map(|i| i as f64).collect::<Vec<f64>>();
+        let (mean, interval) = mean_and_interval(&thirty_one);
+
+        assert_eq!(mean, 15.0);
+        assert!((interval - 2.042 * standard_error(&thirty_one)).abs() < 1e-12);
+
+        let thirty_two = (0..32).map(|i| i as f64).collect::<Vec<f64>>();
+        let (_, interval) = mean_and_interval(&thirty_two);
+
+        assert!((interval - Z_95 * standard_error(&thirty_two)).abs() < 1e-12);
+    }
+}
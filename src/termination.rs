@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use convergence;
+use convergence::Criteria;
+use statistics::Statistics;
+
+
+// Why a run ended.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Reason {
+    Completed,
+    Extinction,
+    FitnessReached,
+    Converged,
+    Stalled,
+    LowDiversity,
+    TimeLimit
+}
+
+impl Reason {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Reason::Completed      => "completed",
+            Reason::Extinction     => "extinction",
+            Reason::FitnessReached => "fitness_reached",
+            Reason::Converged      => "converged",
+            Reason::Stalled        => "stalled",
+            Reason::LowDiversity   => "low_diversity",
+            Reason::TimeLimit      => "time_limit"
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match *self {
+            Reason::Completed      => "ran every generation",
+            Reason::Extinction     => "no giraffe in the tower had any fitness left",
+            Reason::FitnessReached => "mean fitness reached the target",
+            Reason::Converged      => "every trait mean has converged",
+            Reason::Stalled        => "mean fitness stopped improving",
+            Reason::LowDiversity   => "mean allele entropy fell below the minimum",
+            Reason::TimeLimit      => "ran out of time"
+        }
+    }
+}
+
+// Stopping rules checked after every generation. Extinction always ends a
+// run; the rest apply when set.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Rules {
+    pub target_fitness: Option<f64>,
+    pub convergence:    Option<Criteria>,
+    pub stall:          Option<u32>,
+    pub min_diversity:  Option<f64>,
+    pub time_limit:     Option<Duration>
+}
+
+pub struct Termination {
+    rules:           Rules,
+    started:         Instant,
+    best_fitness:    f64,
+    best_generation: u32
+}
+
+impl Termination {
+    pub fn new(rules: Rules) -> Self {
+        Termination {
+            rules:           rules,
+            started:         Instant::now(),
+            best_fitness:    0.0,
+            best_generation: 0
+        }
+    }
+
+    // The reason to stop after the latest generation, if any.
+    pub fn check(&mut self, statistics: &Statistics) -> Option<Reason> {
+        let latest = match statistics.latest() {
+            Some(latest) => latest,
+            None         => return None
+        };
+
+        if latest.generation == 0 || latest.fitness_mean > self.best_fitness {
+            self.best_fitness    = latest.fitness_mean;
+            self.best_generation = latest.generation;
+        }
+
+        if latest.fitness_maximum <= 0.0 {
+            return Some(Reason::Extinction);
+        }
+
+        if self.rules.target_fitness.map_or(false, |target| latest.fitness_mean >= target) {
+            return Some(Reason::FitnessReached);
+        }
+
+        if self.rules.convergence.map_or(false, |criteria| convergence::has_converged(&statistics, criteria)) {
+            return Some(Reason::Converged);
+        }
+
+        if self.rules.stall.map_or(false, |stall| latest.generation - self.best_generation >= stall) {
+            return Some(Reason::Stalled);
+        }
+
+        if self.rules.min_diversity.map_or(false, |minimum| latest.allele_entropy_mean < minimum) {
+            return Some(Reason::LowDiversity);
+        }
+
+        if self.rules.time_limit.map_or(false, |limit| self.started.elapsed() >= limit) {
+            return Some(Reason::TimeLimit);
+        }
+
+        None
+    }
+}
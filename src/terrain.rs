@@ -0,0 +1,283 @@
+use defaults;
+use giraffe;
+use giraffe::Giraffe;
+use options::Options;
+use traits::HasColor;
+use traits::HasHeight;
+use traits::HasSpeed;
+use world;
+
+
+const MAX_SPEED: u32 = 2500;
+
+// Larger grids take too long to evaluate and render.
+pub const MAX_RESOLUTION: usize = 2048;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Phenotype {
+    Legs,
+    Neck,
+    Color,
+    Height,
+    Speed
+}
+
+impl Phenotype {
+    pub fn from_name(name: &str) -> Option<Phenotype> {
+        match name {
+            "legs"   => Some(Phenotype::Legs),
+            "neck"   => Some(Phenotype::Neck),
+            "color"  => Some(Phenotype::Color),
+            "height" => Some(Phenotype::Height),
+            "speed"  => Some(Phenotype::Speed),
+            _        => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Phenotype::Legs   => "legs",
+            Phenotype::Neck   => "neck",
+            Phenotype::Color  => "color",
+            Phenotype::Height => "height",
+            Phenotype::Speed  => "speed"
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match *self {
+            Phenotype::Legs   => "Leg Length",
+            Phenotype::Neck   => "Neck Length",
+            Phenotype::Color  => "Color",
+            Phenotype::Height => "Height",
+            Phenotype::Speed  => "Speed"
+        }
+    }
+
+    pub fn full_range(&self) -> (u32, u32) {
+        match *self {
+            Phenotype::Legs   => (0, giraffe::MAX_LEG_LENGTH),
+            Phenotype::Neck   => (0, giraffe::MAX_NECK_LENGTH),
+            Phenotype::Color  => (0, giraffe::MAX_COLOR),
+            Phenotype::Height => (0, giraffe::MAX_LEG_LENGTH + giraffe::MAX_NECK_LENGTH),
+            Phenotype::Speed  => (0, MAX_SPEED)
+        }
+    }
+
+    pub fn value_of(&self, giraffe: &Giraffe) -> u32 {
+        match *self {
+            Phenotype::Legs   => giraffe.leg_length(),
+            Phenotype::Neck   => giraffe.neck_length(),
+            Phenotype::Color  => giraffe.color(),
+            Phenotype::Height => giraffe.height(),
+            Phenotype::Speed  => giraffe.speed()
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Axis {
+    pub phenotype: Phenotype,
+    pub min:       u32,
+    pub max:       u32
+}
+
+impl Axis {
+    pub fn new(phenotype: Phenotype) -> Self {
+        let (min, max) = phenotype.full_range();
+
+        Axis { phenotype: phenotype, min: min, max: max }
+    }
+
+    pub fn value_at(&self, index: usize, resolution: usize) -> u32 {
+        if resolution < 2 {
+            return self.min;
+        }
+
+        let step = (self.max - self.min) as f64 / (resolution - 1) as f64;
+
+        self.min + (index as f64 * step).round() as u32
+    }
+
+    pub fn index_of(&self, value: u32, resolution: usize) -> usize {
+        if self.max <= self.min || value <= self.min {
+            return 0;
+        }
+
+        let proportion = (value - self.min) as f64 / (self.max - self.min) as f64;
+
+        ((proportion * (resolution - 1) as f64).round() as usize).min(resolution - 1)
+    }
+}
+
+// Traits that aren't on either axis are held at these values. Leaving one
+// unset holds it at its optimum, or derives it from the legs and neck for
+// height and speed.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FixedValues {
+    pub legs:   Option<u32>,
+    pub neck:   Option<u32>,
+    pub color:  Option<u32>,
+    pub height: Option<u32>,
+    pub speed:  Option<u32>
+}
+
+impl FixedValues {
+    pub fn set(&mut self, phenotype: Phenotype, value: u32) {
+        match phenotype {
+            Phenotype::Legs   => self.legs   = Some(value),
+            Phenotype::Neck   => self.neck   = Some(value),
+            Phenotype::Color  => self.color  = Some(value),
+            Phenotype::Height => self.height = Some(value),
+            Phenotype::Speed  => self.speed  = Some(value)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TraitValues {
+    pub color:  u32,
+    pub height: u32,
+    pub speed:  u32
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TerrainSpec {
+    pub x_axis:     Axis,
+    pub y_axis:     Axis,
+    pub resolution: usize,
+    pub fixed:      FixedValues
+}
+
+impl Default for TerrainSpec {
+    fn default() -> Self {
+        TerrainSpec {
+            x_axis:     Axis::new(Phenotype::Neck),
+            y_axis:     Axis::new(Phenotype::Legs),
+            resolution: defaults::TERRAIN_RESOLUTION,
+            fixed:      FixedValues::default()
+        }
+    }
+}
+
+impl TerrainSpec {
+    pub fn x_values(&self) -> Vec<u32> {
+        (0..self.resolution).map(|i| self.x_axis.value_at(i, self.resolution)).collect()
+    }
+
+    pub fn y_values(&self) -> Vec<u32> {
+        (0..self.resolution).map(|i| self.y_axis.value_at(i, self.resolution)).collect()
+    }
+
+    pub fn cell_of(&self, giraffe: &Giraffe) -> (usize, usize) {
+        (
+            self.y_axis.index_of(self.y_axis.phenotype.value_of(&giraffe), self.resolution),
+            self.x_axis.index_of(self.x_axis.phenotype.value_of(&giraffe), self.resolution)
+        )
+    }
+
+    pub fn baseline(&self, options: &Options) -> FixedValues {
+        let mut baseline = self.fixed;
+
+        if baseline.legs.is_none() || baseline.neck.is_none() {
+            let (optimal_legs, optimal_neck) = optimal_lengths(&options);
+
+            baseline.legs = baseline.legs.or(Some(optimal_legs));
+            baseline.neck = baseline.neck.or(Some(optimal_neck));
+        }
+
+        baseline.color = baseline.color.or(Some(options.color));
+
+        baseline
+    }
+
+    pub fn trait_values(&self, baseline: &FixedValues, x: u32, y: u32) -> TraitValues {
+        let mut fixed = *baseline;
+
+        fixed.set(self.x_axis.phenotype, x);
+        fixed.set(self.y_axis.phenotype, y);
+
+        let giraffe = Giraffe::new_from_phenotypic_values(
+            fixed.color.unwrap(), fixed.legs.unwrap(), fixed.neck.unwrap()
+        );
+
+        TraitValues {
+            color:  giraffe.color(),
+            height: fixed.height.unwrap_or(giraffe.height()),
+            speed:  fixed.speed.unwrap_or(giraffe.speed())
+        }
+    }
+}
+
+// Rows follow the y axis and columns the x axis.
+pub fn create_trait_matrix(spec: &TerrainSpec, options: &Options) -> Vec<Vec<TraitValues>> {
+    let x_values = spec.x_values();
+    let baseline = spec.baseline(&options);
+
+    spec.y_values().into_iter().map(|y| {
+        x_values.iter().map(|&x| {
+            spec.trait_values(&baseline, x, y)
+        }).collect::<Vec<TraitValues>>()
+    }).collect::<Vec<Vec<TraitValues>>>()
+}
+
+pub fn create_fitness_matrix(spec: &TerrainSpec, options: &Options) -> Vec<Vec<f32>> {
+    create_trait_matrix(&spec, &options).iter().map(|row| {
+        row.iter().map(|values| {
+            world::calculate_phenotype_fitness(&options, values.color, values.height, values.speed)
+        }).collect::<Vec<f32>>()
+    }).collect::<Vec<Vec<f32>>>()
+}
+
+pub fn optimal_lengths(options: &Options) -> (u32, u32) {
+    let mut best         = (0, 0);
+    let mut best_fitness = -1.0;
+
+    for legs in 0..(giraffe::MAX_LEG_LENGTH + 1) {
+        for neck in 0..(giraffe::MAX_NECK_LENGTH + 1) {
+            let speed   = giraffe::calculate_speed(legs, neck);
+            let fitness = world::calculate_phenotype_fitness(&options, options.color, legs + neck, speed);
+
+            if fitness > best_fitness {
+                best         = (legs, neck);
+                best_fitness = fitness;
+            }
+        }
+    }
+
+    best
+}
+
+pub fn parse_range(range: &str) -> Option<(u32, u32)> {
+    let bounds = range.split(':').map(|bound| {
+        bound.trim().parse::<u32>().ok()
+    }).collect::<Vec<Option<u32>>>();
+
+    if bounds.len() != 2 {
+        return None;
+    }
+
+    match (bounds[0], bounds[1]) {
+        (Some(min), Some(max)) if min < max => Some((min, max)),
+        _                                   => None
+    }
+}
+
+pub fn parse_fixed_values(fixed: &str) -> Option<FixedValues> {
+    let mut values = FixedValues::default();
+
+    for assignment in fixed.split(',').filter(|assignment| !assignment.trim().is_empty()) {
+        let parts = assignment.split('=').map(|part| part.trim()).collect::<Vec<&str>>();
+
+        if parts.len() != 2 {
+            return None;
+        }
+
+        match (Phenotype::from_name(parts[0]), parts[1].parse::<u32>()) {
+            (Some(phenotype), Ok(value)) => values.set(phenotype, value),
+            _                            => return None
+        }
+    }
+
+    Some(values)
+}
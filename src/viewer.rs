@@ -0,0 +1,206 @@
+use glfw::{Action, Key, WindowEvent};
+use kiss3d;
+use nalgebra;
+
+use std::rc::Rc;
+
+use giraffe;
+use options::Options;
+use solution_space;
+use solution_space::HEIGHT_SCALE;
+use terrain;
+use terrain::{Phenotype, TerrainSpec};
+
+
+const TREE_HEIGHT_STEP: i32 = 50;
+const LION_SPEED_STEP:  i32 = 50;
+const COLOR_STEP:       i32 = 25;
+const ROTATION_SPEED:   f32 = 0.002;
+const TICK_LENGTH:      f32 = 0.3;
+const TEXT_LINE_HEIGHT: f32 = 40.0;
+
+const X_AXIS_COLOR: (f32, f32, f32) = (1.0, 0.3, 0.3);
+const Y_AXIS_COLOR: (f32, f32, f32) = (0.3, 1.0, 0.3);
+const Z_AXIS_COLOR: (f32, f32, f32) = (0.4, 0.6, 1.0);
+const TEXT_COLOR:   (f32, f32, f32) = (1.0, 1.0, 1.0);
+
+#[derive(Copy, Clone, Debug)]
+enum Command {
+    TreeHeight(i32),
+    LionSpeed(i32),
+    Color(i32),
+    ToggleRotation,
+    Screenshot
+}
+
+fn command_for(key: Key) -> Option<Command> {
+    match key {
+        Key::Up       => Some(Command::TreeHeight(TREE_HEIGHT_STEP)),
+        Key::Down     => Some(Command::TreeHeight(-TREE_HEIGHT_STEP)),
+        Key::Right    => Some(Command::LionSpeed(LION_SPEED_STEP)),
+        Key::Left     => Some(Command::LionSpeed(-LION_SPEED_STEP)),
+        Key::PageUp   => Some(Command::Color(COLOR_STEP)),
+        Key::PageDown => Some(Command::Color(-COLOR_STEP)),
+        Key::R        => Some(Command::ToggleRotation),
+        Key::S        => Some(Command::Screenshot),
+        _             => None
+    }
+}
+
+// Targets stay at least 1, as a zero target leaves fitness undefined.
+fn adjust(value: u32, delta: i32, maximum: u32) -> u32 {
+    (value as i32 + delta).max(1).min(maximum as i32) as u32
+}
+
+fn max_fitness(matrix: &Vec<Vec<f32>>) -> f32 {
+    matrix.iter().fold(0.0, |acc, row| {
+        row.iter().fold(acc, |acc, &fitness| acc.max(fitness))
+    })
+}
+
+fn point((x, y, z): (f32, f32, f32)) -> nalgebra::Point3<f32> {
+    nalgebra::Point3::new(x, y, z)
+}
+
+// The mesh puts the y axis trait along the world's x axis and the x axis trait
+// along the world's y axis, so the gizmos follow the same layout.
+fn draw_axes(window: &mut kiss3d::window::Window, spec: &TerrainSpec, max_fitness: f32) {
+    let origin = solution_space::create_mesh_point(spec.y_axis.min as f32, spec.x_axis.min as f32, 0.0);
+    let x_end  = solution_space::create_mesh_point(spec.y_axis.min as f32, spec.x_axis.max as f32, 0.0);
+    let y_end  = solution_space::create_mesh_point(spec.y_axis.max as f32, spec.x_axis.min as f32, 0.0);
+    let top    = max_fitness.ceil().max(1.0);
+
+    window.draw_line(&origin, &x_end, &point(X_AXIS_COLOR));
+    window.draw_line(&origin, &y_end, &point(Y_AXIS_COLOR));
+    window.draw_line(&origin, &nalgebra::Point3::new(origin.x, origin.y, top * HEIGHT_SCALE), &point(Z_AXIS_COLOR));
+
+    for tick in 1..(top as u32 + 1) {
+        let z = tick as f32 * HEIGHT_SCALE;
+
+        window.draw_line(
+            &nalgebra::Point3::new(origin.x, origin.y, z),
+            &nalgebra::Point3::new(origin.x - TICK_LENGTH, origin.y - TICK_LENGTH, z),
+            &point(Z_AXIS_COLOR)
+        );
+    }
+}
+
+fn draw_legend(
+    window:      &mut kiss3d::window::Window,
+    font:        &Rc<kiss3d::text::Font>,
+    spec:        &TerrainSpec,
+    options:     &Options,
+    is_rotating: bool
+) {
+    let lines = vec![
+        (format!("x: {} {}-{}", spec.x_axis.phenotype.label(), spec.x_axis.min, spec.x_axis.max), X_AXIS_COLOR),
+        (format!("y: {} {}-{}", spec.y_axis.phenotype.label(), spec.y_axis.min, spec.y_axis.max), Y_AXIS_COLOR),
+        ("z: Fitness, one tick per 1.0".to_string(), Z_AXIS_COLOR),
+        (format!("Tree height: {} (up/down)", options.tree_height), TEXT_COLOR),
+        (format!("Lion speed: {} (left/right)", options.lion_speed), TEXT_COLOR),
+        (format!("Color: {} (page up/page down)", options.color), TEXT_COLOR),
+        (format!("Rotation: {} (r), screenshot (s)", if is_rotating { "on" } else { "off" }), TEXT_COLOR)
+    ];
+
+    for (i, (text, color)) in lines.into_iter().enumerate() {
+        window.draw_text(
+            &text,
+            &nalgebra::Point2::new(10.0, 10.0 + i as f32 * TEXT_LINE_HEIGHT),
+            &font,
+            &point(color)
+        );
+    }
+}
+
+pub fn render_3d(spec: &TerrainSpec, mut options: Options) {
+    let mut spec = *spec;
+
+    // The giraffes keep their starting color, so that changing the
+    // environment's color moves the terrain instead of both at once.
+    if spec.fixed.color.is_none() {
+        spec.fixed.color = Some(options.color);
+    }
+
+    let mut window = kiss3d::window::Window::new("Fitness Terrain");
+    let font       = kiss3d::text::Font::default();
+
+    let center = solution_space::create_mesh_point(
+        (spec.y_axis.min + spec.y_axis.max) as f32 / 2.0,
+        (spec.x_axis.min + spec.x_axis.max) as f32 / 2.0,
+        0.0
+    );
+
+    let mut camera = kiss3d::camera::ArcBall::new(
+        nalgebra::Point3::new(center.x - 25.0, center.y - 25.0, 20.0),
+        center
+    );
+
+    let matrix = terrain::create_fitness_matrix(&spec, &options);
+
+    let mut peak_fitness = max_fitness(&matrix);
+    let mut group        = window.add_group();
+    let mut mesh         = solution_space::add_terrain(&mut group, &spec, &matrix);
+
+    let mut is_rotating = true;
+    let mut screenshots = 0;
+
+    window.set_light(kiss3d::light::Light::StickToCamera);
+
+    while window.render_with_camera(&mut camera) {
+        let mut is_stale = false;
+
+        for mut event in window.events().iter() {
+            let command = match event.value {
+                WindowEvent::Key(key, _, Action::Press, _) => command_for(key),
+                _                                          => None
+            };
+
+            match command {
+                Some(Command::TreeHeight(delta)) => {
+                    options.tree_height = adjust(options.tree_height, delta, Phenotype::Height.full_range().1);
+                    is_stale = true;
+                },
+                Some(Command::LionSpeed(delta)) => {
+                    options.lion_speed = adjust(options.lion_speed, delta, Phenotype::Speed.full_range().1);
+                    is_stale = true;
+                },
+                Some(Command::Color(delta)) => {
+                    options.color = adjust(options.color, delta, giraffe::MAX_COLOR);
+                    is_stale = true;
+                },
+                Some(Command::ToggleRotation) => is_rotating = !is_rotating,
+                Some(Command::Screenshot) => {
+                    screenshots += 1;
+
+                    let destination = format!("terrain_screenshot_{:03}.png", screenshots);
+
+                    match window.snap_image().save(&destination) {
+                        Ok(_)      => println!("Saved screenshot to {:?}", destination),
+                        Err(error) => println!("Failed to save screenshot: {}", error)
+                    }
+                },
+                None => continue
+            }
+
+            event.inhibited = true;
+        }
+
+        if is_stale {
+            let matrix = terrain::create_fitness_matrix(&spec, &options);
+
+            peak_fitness = max_fitness(&matrix);
+
+            mesh.unlink();
+            mesh = solution_space::add_terrain(&mut group, &spec, &matrix);
+        }
+
+        if is_rotating {
+            let yaw = camera.yaw();
+
+            camera.set_yaw(yaw + ROTATION_SPEED);
+        }
+
+        draw_axes(&mut window, &spec, peak_fitness);
+        draw_legend(&mut window, &font, &spec, &options, is_rotating);
+    }
+}
@@ -1,40 +1,56 @@
 use std::cmp::Ordering;
 
+use canopy::{Canopy, ForageModel};
+use competition::apply_competition;
 use giraffe::Giraffe;
 use giraffe_lib::random_proportion;
+use mating::{choose_mate, Mating};
 use mutation::calculate_mutation_rate;
 use options;
+use selection::apply_selection;
 use traits::CanMate;
 use traits::HasColor;
 use traits::HasHeight;
 use traits::HasSpeed;
 
 
+// Foraging counts one and a half, color and speed one each.
+pub const MAX_FITNESS: f32 = 3.5;
+
 pub struct World {
     pub generation: u32,
     pub options:    options::Options,
-    pub tower:      Vec<Giraffe>
+    pub tower:      Vec<Giraffe>,
+    pub parents:    Vec<(usize, usize)>,
+    next_id:        u64
 }
 
 impl World {
     pub fn new(options: options::Options) -> Self {
         let tower: Vec<Giraffe> = (0..options.herd_size).map(|_| {
-            Giraffe::random()
+            Giraffe::random_with(options.markers, options.mating == Mating::Preference)
         }).collect();
 
         World::new_from_tower(tower, options)
     }
 
-    pub fn new_from_tower(tower: Vec<Giraffe>, options: options::Options) -> Self {
+    pub fn new_from_tower(mut tower: Vec<Giraffe>, options: options::Options) -> Self {
+        for (id, giraffe) in tower.iter_mut().enumerate() {
+            giraffe.id         = id as u64;
+            giraffe.parent_ids = None;
+        }
+
         World {
             generation: 0,
             options:    options,
-            tower:      tower
+            next_id:    tower.len() as u64,
+            tower:      tower,
+            parents:    vec![]
         }
     }
 
     pub fn evolve(&self) -> Self {
-        let tower = self.next_tower();
+        let (tower, parents) = self.next_tower();
 
         let tree_height = if random_proportion() < 0.0001 {
             (random_proportion() * 1500.0 + 500.0) as u32
@@ -45,11 +61,13 @@ impl World {
         World {
             generation: self.generation + 1,
             options:    options::Options { tree_height: tree_height, ..self.options },
-            tower:      tower
+            next_id:    self.next_id + tower.len() as u64,
+            tower:      tower,
+            parents:    parents
         }
     }
 
-    fn next_tower(&self) -> Vec<Giraffe> {
+    fn next_tower(&self) -> (Vec<Giraffe>, Vec<(usize, usize)>) {
         let mutation_rate = calculate_mutation_rate(
             self.options.mutation_decay,
             self.options.mutation_rate,
@@ -64,39 +82,79 @@ impl World {
 
         let cumulative_densities = generate_cumulative_densities(fitnesses);
 
-        (0..self.options.herd_size).map(|_| {
-            let giraffe1 = select_giraffe(
-                &cumulative_densities,
-                &self.tower
+        let parents = (0..self.options.herd_size).map(|_| {
+            let parent1 = select_giraffe(&cumulative_densities);
+            let parent2 = choose_mate(&self.options, &self.tower, parent1, || {
+                select_giraffe(&cumulative_densities)
+            });
+
+            (parent1, parent2)
+        }).collect::<Vec<(usize, usize)>>();
+
+        let tower = parents.iter().enumerate().map(|(i, &(parent1, parent2))| {
+            let mut child = Giraffe::mate(
+                &self.tower[parent1],
+                &self.tower[parent2],
+                mutation_rate,
+                self.options.blending_mode
             );
 
-            let giraffe2 = select_giraffe(
-                &cumulative_densities,
-                &self.tower
-            );
+            child.id = self.next_id + i as u64;
+            child
+        }).collect::<Vec<Giraffe>>();
 
-            Giraffe::mate(giraffe1, giraffe2, mutation_rate, self.options.blending_mode)
-        }).collect::<Vec<Giraffe>>()
+        (tower, parents)
     }
 }
 
 pub fn calculate_fitnesses(world: &World, tower: &Vec<Giraffe>) -> Vec<f32> {
-    tower.iter().map(|giraffe| {
-        calculate_fitness(&world, &giraffe)
-    }).collect::<Vec<f32>>()
+    let forage_fitnesses = calculate_forage_fitnesses(&world, &tower);
+
+    let fitnesses = tower.iter().zip(forage_fitnesses).map(|(giraffe, forage_fitness)| {
+        calculate_fitness(&world.options, giraffe.color(), giraffe.speed(), forage_fitness)
+    }).collect::<Vec<f32>>();
+
+    let fitnesses = apply_competition(
+        world.options.competition,
+        world.options.competition_strength,
+        &tower,
+        fitnesses
+    );
+
+    apply_selection(world.options.selection, fitnesses)
 }
 
-fn calculate_fitness(world: &World, giraffe: &Giraffe) -> f32 {
+fn calculate_forage_fitnesses(world: &World, tower: &Vec<Giraffe>) -> Vec<f32> {
+    match world.options.forage_model {
+        ForageModel::TreeHeight => tower.iter().map(|giraffe| {
+            calculate_partial_fitness(giraffe.height() as i32, world.options.tree_height as i32)
+        }).collect::<Vec<f32>>(),
+        ForageModel::Canopy => {
+            let canopy = Canopy::new(world.options.tree_height, tower.len());
+
+            canopy.forage(&tower)
+        }
+    }
+}
+
+// The fitness of a lone phenotype against the environment, leaving out every
+// term that depends on the rest of the tower.
+pub fn calculate_phenotype_fitness(options: &options::Options, color: u32, height: u32, speed: u32) -> f32 {
+    let forage_fitness = calculate_partial_fitness(height as i32, options.tree_height as i32);
+
+    calculate_fitness(&options, color, speed, forage_fitness)
+}
+
+fn calculate_fitness(options: &options::Options, color: u32, speed: u32, forage_fitness: f32) -> f32 {
     vec![
-        (giraffe.color()  as i32, world.options.color       as i32, 1.0),
-        (giraffe.height() as i32, world.options.tree_height as i32, 1.5),
-        (giraffe.speed()  as i32, world.options.lion_speed  as i32, 1.0)
+        (color as i32, options.color      as i32, 1.0),
+        (speed as i32, options.lion_speed as i32, 1.0)
     ]
         .into_iter()
         .map(|(phenotype, environment, weight)| {
             calculate_partial_fitness(phenotype, environment) * weight
         })
-        .fold(0.0, |acc, proportion| acc + proportion)
+        .fold(forage_fitness * 1.5, |acc, proportion| acc + proportion)
 }
 
 fn calculate_partial_fitness(phenotype: i32, environment: i32) -> f32 {
@@ -128,7 +186,7 @@ fn generate_cumulative_densities(fitnesses: Vec<f32>) -> Vec<(f64, f64)> {
     cds
 }
 
-fn select_giraffe<'a>(cumulative_densities: &Vec<(f64, f64)>, tower: &'a Vec<Giraffe>) -> &'a Giraffe {
+fn select_giraffe(cumulative_densities: &Vec<(f64, f64)>) -> usize {
     let search_value  = (random_proportion() as f64) * (cumulative_densities.last().unwrap().1 - 1.0);
     let search_result = cumulative_densities.binary_search_by(|&(min, max)| {
         if min > search_value {
@@ -142,10 +200,8 @@ fn select_giraffe<'a>(cumulative_densities: &Vec<(f64, f64)>, tower: &'a Vec<Gir
         }
     });
 
-    let matching_index = match search_result {
+    match search_result {
         Ok(i) => i,
         _     => 0
-    };
-
-    &tower[matching_index]
+    }
 }